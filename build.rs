@@ -4,7 +4,7 @@ fn main() {
     protobuf_codegen::Codegen::new()
         .protoc()
         .protoc_path(&protoc_bin_vendored::protoc_bin_path().unwrap())
-        .includes(&["proto"])
+        .includes(["proto"])
         .input("proto/ais/v1/spec.proto")
         .cargo_out_dir("proto_generated")
         .run_from_script();
@@ -0,0 +1,532 @@
+//! Column-oriented archive for long-term storage. Position reports are
+//! decoded into one column per field, so similar values sit next to each
+//! other and pack tightly. Every row keeps the `Encoded` metadata it would
+//! have been sent with, so the original lines can be regenerated exactly.
+
+use bit_struct::u3;
+use protobuf::{Enum, MessageField};
+
+use crate::bits::{BitReader, BitWriter};
+use crate::message::{
+    CourseOverGround, Heading, NavigationStatus, Payload, Position, PositionReport, RateOfTurn,
+    SpeedOverGround,
+};
+use crate::mmsi::Mmsi;
+use crate::proto::spec;
+
+/// Version 2 added tag blocks
+pub const VERSION: u32 = 2;
+
+// Row kinds
+const KIND_RAW: i64 = 0;
+const KIND_BITS: i64 = 1;
+const KIND_POSITION: i64 = 2;
+
+// How the metadata of an encoded row was sent
+const METADATA_PACKED: i64 = 0;
+const METADATA_FULL: i64 = 1;
+const METADATA_TALKER: i64 = 2;
+
+const TRAILING_WHITESPACE: i64 = 4;
+const TAG_BLOCK: i64 = 8;
+
+/// Packs `values` into a column. Delta encoding suits slowly changing values
+/// such as time and position, the default offset from the minimum suits
+/// values clustered in a range.
+pub fn pack_column(values: &[i64], delta: bool) -> spec::Column {
+    let base = if delta {
+        values.first().copied().unwrap_or_default()
+    } else {
+        values.iter().copied().min().unwrap_or_default()
+    };
+    let mut prev = base;
+    let encoded: Vec<u64> = values
+        .iter()
+        .map(|&v| {
+            if delta {
+                let diff = v.wrapping_sub(prev);
+                prev = v;
+                ((diff << 1) ^ (diff >> 63)) as u64
+            } else {
+                v.wrapping_sub(base) as u64
+            }
+        })
+        .collect();
+    let width = encoded
+        .iter()
+        .map(|v| 64 - v.leading_zeros())
+        .max()
+        .unwrap_or_default()
+        // At least a bit per value, so the data bounds the count on reading
+        .max(1);
+
+    let mut w = BitWriter::new();
+    for v in encoded {
+        w.write_u(width as usize, v);
+    }
+    let mut column = spec::Column::new();
+    column.set_count(values.len() as u32);
+    column.set_width(width);
+    if delta {
+        column.set_delta(true);
+    }
+    column.set_base(base);
+    column.set_data(w.finish().0);
+    column
+}
+
+pub fn unpack_column(column: &spec::Column) -> anyhow::Result<Vec<i64>> {
+    let width = column.width() as usize;
+    if width > 64 {
+        anyhow::bail!("column width {width} too large");
+    }
+    let count = column.count() as usize;
+    if count > 0 && (width == 0 || count > column.data().len() * 8 / width) {
+        anyhow::bail!(
+            "column of {count} values doesn't fit in {} bytes",
+            column.data().len()
+        );
+    }
+    let mut r = BitReader::new(column.data(), u3::new(0).unwrap());
+    let mut prev = column.base();
+    (0..column.count())
+        .map(|_| {
+            let v = r.read_u(width)?;
+            Ok(if column.delta() {
+                let diff = (v >> 1) as i64 ^ -((v & 1) as i64);
+                prev = prev.wrapping_add(diff);
+                prev
+            } else {
+                column.base().wrapping_add(v as i64)
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Default)]
+struct Columns {
+    kind: Vec<i64>,
+    time: Vec<i64>,
+    ending: Vec<i64>,
+    texts: Vec<String>,
+    metadata_form: Vec<i64>,
+    packed_metadata: Vec<i64>,
+    metadata: Vec<i64>,
+    drop_bits: Vec<i64>,
+    payloads: Vec<Vec<u8>>,
+    msg_type: Vec<i64>,
+    repeat: Vec<i64>,
+    mmsi: Vec<i64>,
+    status: Vec<i64>,
+    rate_of_turn: Vec<i64>,
+    speed: Vec<i64>,
+    accuracy: Vec<i64>,
+    lon: Vec<i64>,
+    lat: Vec<i64>,
+    course: Vec<i64>,
+    heading: Vec<i64>,
+    timestamp: Vec<i64>,
+    maneuver: Vec<i64>,
+    raim: Vec<i64>,
+    radio: Vec<i64>,
+}
+
+impl Columns {
+    fn push_position(&mut self, m: &PositionReport) {
+        self.msg_type.push(m.msg_type.into());
+        self.repeat.push(m.repeat.into());
+        self.mmsi.push(m.mmsi.0.into());
+        self.status.push(u8::from(m.status).into());
+        self.rate_of_turn.push(m.rate_of_turn.0.into());
+        self.speed.push(m.speed.0.into());
+        self.accuracy.push(m.accuracy.into());
+        self.lon.push(m.position.lon.into());
+        self.lat.push(m.position.lat.into());
+        self.course.push(m.course.0.into());
+        self.heading.push(m.heading.0.into());
+        self.timestamp.push(m.timestamp.into());
+        self.maneuver.push(m.maneuver.into());
+        self.raim.push(m.raim.into());
+        self.radio.push(m.radio.into());
+    }
+}
+
+/// Collects lines into an [`spec::ArchiveChunk`].
+#[derive(Debug, Default)]
+pub struct ArchiveWriter {
+    rows: usize,
+    columns: Columns,
+}
+
+impl ArchiveWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    /// Adds a line, including its line ending, received at `time`
+    /// milliseconds since the Unix epoch.
+    pub fn push(&mut self, time: i64, line: &str) -> anyhow::Result<()> {
+        let (content, trailing_whitespace, line_ending) = crate::proto::split_line(line);
+        let c = &mut self.columns;
+        self.rows += 1;
+        c.time.push(time);
+
+        let message = content
+            .parse::<spec::Message>()
+            .unwrap_or_else(|e| match e {});
+        // Anything which doesn't reproduce exactly is kept as text
+        let encoded = message.has_encoded()
+            && message
+                .try_to_string()
+                .is_ok_and(|regenerated| regenerated == content);
+
+        let mut ending = i64::from(line_ending.value());
+        if !trailing_whitespace.is_empty() {
+            ending |= TRAILING_WHITESPACE;
+            c.texts.push(trailing_whitespace.to_owned());
+        }
+        if encoded && message.has_tag_block() {
+            ending |= TAG_BLOCK;
+            c.texts.push(message.tag_block().to_owned());
+        }
+        c.ending.push(ending);
+
+        if !encoded {
+            c.kind.push(KIND_RAW);
+            c.texts.push(content.to_owned());
+            return Ok(());
+        }
+
+        let e = message.encoded();
+        if e.has_packed_metadata() {
+            c.metadata_form.push(METADATA_PACKED);
+            c.packed_metadata.push(e.packed_metadata() as i64);
+        } else {
+            c.metadata.push(e.metadata() as i64);
+            if e.has_talker() {
+                c.metadata_form.push(METADATA_TALKER);
+                c.texts.push(e.talker().to_owned());
+            } else {
+                c.metadata_form.push(METADATA_FULL);
+            }
+        }
+
+        let drop_bits = e.metadata_fields()?.drop_bits;
+        match PositionReport::from_bits(e.body(), drop_bits) {
+            // Spare bits which aren't zero would be lost by re-encoding
+            Ok(m) if m.to_bits() == (e.body().to_vec(), drop_bits) => {
+                c.kind.push(KIND_POSITION);
+                c.push_position(&m);
+            }
+            _ => {
+                c.kind.push(KIND_BITS);
+                c.drop_bits.push(drop_bits.value().into());
+                c.payloads.push(e.body().to_vec());
+            }
+        }
+        Ok(())
+    }
+
+    /// Packs the collected rows, leaving the writer empty.
+    pub fn finish(&mut self) -> spec::ArchiveChunk {
+        let c = std::mem::take(&mut self.columns);
+        let column = |values: &[i64], delta| MessageField::some(pack_column(values, delta));
+
+        let mut chunk = spec::ArchiveChunk::new();
+        chunk.set_version(VERSION);
+        chunk.set_rows(self.rows as u32);
+        chunk.kind = column(&c.kind, false);
+        chunk.time = column(&c.time, true);
+        chunk.ending = column(&c.ending, false);
+        chunk.texts = c.texts;
+        chunk.metadata_form = column(&c.metadata_form, false);
+        chunk.packed_metadata = column(&c.packed_metadata, false);
+        chunk.metadata = column(&c.metadata, false);
+        chunk.drop_bits = column(&c.drop_bits, false);
+        chunk.payloads = c.payloads;
+        chunk.msg_type = column(&c.msg_type, false);
+        chunk.repeat = column(&c.repeat, false);
+        chunk.mmsi = column(&c.mmsi, false);
+        chunk.status = column(&c.status, false);
+        chunk.rate_of_turn = column(&c.rate_of_turn, false);
+        chunk.speed = column(&c.speed, true);
+        chunk.accuracy = column(&c.accuracy, false);
+        chunk.lon = column(&c.lon, true);
+        chunk.lat = column(&c.lat, true);
+        chunk.course = column(&c.course, false);
+        chunk.heading = column(&c.heading, false);
+        chunk.timestamp = column(&c.timestamp, false);
+        chunk.maneuver = column(&c.maneuver, false);
+        chunk.raim = column(&c.raim, false);
+        chunk.radio = column(&c.radio, false);
+        self.rows = 0;
+        chunk
+    }
+}
+
+/// A line read back from an archive.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    /// Milliseconds since the Unix epoch
+    pub time: i64,
+    /// The original line, including its line ending
+    pub line: String,
+    pub report: Option<PositionReport>,
+}
+
+/// Reads values from an unpacked column in order.
+struct ColumnReader<T>(std::vec::IntoIter<T>);
+
+impl<T> ColumnReader<T> {
+    fn next(&mut self) -> anyhow::Result<T> {
+        self.0
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("column ended early"))
+    }
+}
+
+fn reader(column: &spec::Column) -> anyhow::Result<ColumnReader<i64>> {
+    Ok(ColumnReader(unpack_column(column)?.into_iter()))
+}
+
+pub fn read_chunk(chunk: &spec::ArchiveChunk) -> anyhow::Result<Vec<Row>> {
+    if chunk.version() > VERSION {
+        anyhow::bail!("unsupported archive version {}", chunk.version());
+    }
+    let mut kind = reader(&chunk.kind)?;
+    let mut time = reader(&chunk.time)?;
+    let mut ending = reader(&chunk.ending)?;
+    let mut texts = ColumnReader(chunk.texts.clone().into_iter());
+    let mut metadata_form = reader(&chunk.metadata_form)?;
+    let mut packed_metadata = reader(&chunk.packed_metadata)?;
+    let mut metadata = reader(&chunk.metadata)?;
+    let mut drop_bits = reader(&chunk.drop_bits)?;
+    let mut payloads = ColumnReader(chunk.payloads.clone().into_iter());
+    let mut msg_type = reader(&chunk.msg_type)?;
+    let mut repeat = reader(&chunk.repeat)?;
+    let mut mmsi = reader(&chunk.mmsi)?;
+    let mut status = reader(&chunk.status)?;
+    let mut rate_of_turn = reader(&chunk.rate_of_turn)?;
+    let mut speed = reader(&chunk.speed)?;
+    let mut accuracy = reader(&chunk.accuracy)?;
+    let mut lon = reader(&chunk.lon)?;
+    let mut lat = reader(&chunk.lat)?;
+    let mut course = reader(&chunk.course)?;
+    let mut heading = reader(&chunk.heading)?;
+    let mut timestamp = reader(&chunk.timestamp)?;
+    let mut maneuver = reader(&chunk.maneuver)?;
+    let mut raim = reader(&chunk.raim)?;
+    let mut radio = reader(&chunk.radio)?;
+
+    // Every row has a kind, and its column is bounded by the data it came in
+    let count = chunk.rows() as usize;
+    if count != kind.0.len() {
+        anyhow::bail!("chunk of {count} rows has {} row kinds", kind.0.len());
+    }
+    let mut rows = Vec::with_capacity(count);
+    for _ in 0..chunk.rows() {
+        let time = time.next()?;
+        let ending = ending.next()?;
+        let line_ending =
+            spec::LineEnding::from_i32((ending & !(TRAILING_WHITESPACE | TAG_BLOCK)) as i32)
+                .ok_or_else(|| anyhow::anyhow!("invalid line ending {ending}"))?;
+        let trailing_whitespace = if ending & TRAILING_WHITESPACE != 0 {
+            texts.next()?
+        } else {
+            String::new()
+        };
+        let tag_block = if ending & TAG_BLOCK != 0 {
+            Some(texts.next()?)
+        } else {
+            None
+        };
+
+        let kind = kind.next()?;
+        let (mut message, report) = if kind == KIND_RAW {
+            (spec::Message::from(texts.next()?), None)
+        } else {
+            let mut e = spec::Encoded::new();
+            match metadata_form.next()? {
+                METADATA_PACKED => e.set_packed_metadata(packed_metadata.next()? as u64),
+                METADATA_FULL => e.set_metadata(metadata.next()? as u64),
+                METADATA_TALKER => {
+                    e.set_metadata(metadata.next()? as u64);
+                    e.set_talker(texts.next()?);
+                }
+                form => anyhow::bail!("invalid metadata form {form}"),
+            }
+            let report = match kind {
+                KIND_BITS => {
+                    e.set_body(payloads.next()?);
+                    if e.metadata_fields()?.drop_bits.value() as i64 != drop_bits.next()? {
+                        anyhow::bail!("drop bits don't match metadata");
+                    }
+                    None
+                }
+                KIND_POSITION => {
+                    let m = PositionReport {
+                        msg_type: msg_type.next()? as u8,
+                        repeat: repeat.next()? as u8,
+                        mmsi: Mmsi(mmsi.next()? as u32),
+                        status: NavigationStatus::from(status.next()? as u8),
+                        rate_of_turn: RateOfTurn(rate_of_turn.next()? as i8),
+                        speed: SpeedOverGround(speed.next()? as u16),
+                        accuracy: accuracy.next()? != 0,
+                        position: Position {
+                            lon: lon.next()? as i32,
+                            lat: lat.next()? as i32,
+                        },
+                        course: CourseOverGround(course.next()? as u16),
+                        heading: Heading(heading.next()? as u16),
+                        timestamp: timestamp.next()? as u8,
+                        maneuver: maneuver.next()? as u8,
+                        raim: raim.next()? != 0,
+                        radio: radio.next()? as u32,
+                    };
+                    e.set_body(m.to_bits().0);
+                    Some(m)
+                }
+                kind => anyhow::bail!("invalid row kind {kind}"),
+            };
+            let mut message = spec::Message::new();
+            message.set_encoded(e);
+            (message, report)
+        };
+
+        message.set_ending(&trailing_whitespace, line_ending);
+        if let Some(tag_block) = tag_block {
+            message.set_tag_block(tag_block);
+        }
+        let mut line = Vec::new();
+        message.try_write(&mut line)?;
+        message.write_ending(&mut line)?;
+        rows.push(Row {
+            time,
+            line: String::from_utf8(line)?,
+            report,
+        });
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_columns() {
+        let cases: [&[i64]; 5] = [
+            &[],
+            &[7, 7, 7],
+            &[227006760, 227006769, 227006761],
+            &[1_700_000_000_000, 1_700_000_000_250, 1_700_000_000_100],
+            &[i64::MIN, 0, i64::MAX, -1],
+        ];
+        for values in cases {
+            for delta in [false, true] {
+                let column = pack_column(values, delta);
+                assert_eq!(unpack_column(&column).unwrap(), values, "{delta}");
+            }
+        }
+        assert_eq!(pack_column(&[7, 7, 7], false).width(), 1);
+        assert_eq!(pack_column(&[227006760, 227006769], false).width(), 4);
+        let time = pack_column(&[1_700_000_000_000, 1_700_000_000_250], true);
+        assert_eq!(time.width(), 9);
+
+        // Counts the data can't hold, which would otherwise allocate freely
+        let mut column = pack_column(&[1, 2, 3], false);
+        column.set_count(u32::MAX);
+        assert!(unpack_column(&column).is_err());
+        column.set_count(8);
+        assert!(unpack_column(&column).is_err());
+        column.set_width(0);
+        column.set_count(1);
+        assert!(unpack_column(&column).is_err());
+        column.set_count(0);
+        assert!(unpack_column(&column).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let lines = [
+            "!AIVDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*23\n",
+            "!AIVDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*24\r\n",
+            "!AIVDO,1,1,,,B>eq`d@0;6Cb0>4tL<Ghowq5wP06,0*39 \t\n",
+            "!XYVDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*2A\n",
+            "!AIVDM,2,1,3,B,55?MbV02;H;s<HtKR20EHE:0@T4@Dn2222222216L961O5Gf0NSQEp6ClRp8,0*1C\n",
+            "$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A\n",
+            "\n",
+            "not nmea at all",
+        ];
+        let mut writer = ArchiveWriter::new();
+        for (i, line) in lines.iter().enumerate() {
+            writer
+                .push(1_700_000_000_000 + 100 * i as i64, line)
+                .unwrap();
+        }
+        assert_eq!(writer.len(), lines.len());
+        let chunk = writer.finish();
+        assert!(writer.is_empty());
+
+        let rows = read_chunk(&chunk).unwrap();
+        for (i, (row, line)) in rows.iter().zip(lines).enumerate() {
+            assert_eq!(row.line, line);
+            assert_eq!(row.time, 1_700_000_000_000 + 100 * i as i64);
+        }
+        assert_eq!(rows.len(), lines.len());
+        assert_eq!(rows[0].report.as_ref().unwrap().mmsi, Mmsi(227006760));
+        assert!(rows[4].report.is_none());
+        assert_eq!(chunk.kind.count(), lines.len() as u32);
+        assert_eq!(chunk.mmsi.count(), 3);
+    }
+
+    #[test]
+    fn test_tag_blocks() {
+        let lines = [
+            "\\s:r003669945,c:1241544035*4A\\!AIVDM,1,1,,B,15N4cJ`005Jrek0H@9n`DW5608EP,0*13\r\n",
+            "\\c:1241544036*4A\\!AIVDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*23 \n",
+            "\\c:1241544037*4A\\not nmea\n",
+        ];
+        let mut writer = ArchiveWriter::new();
+        for line in lines {
+            writer.push(0, line).unwrap();
+        }
+        let chunk = writer.finish();
+        assert_eq!(chunk.mmsi.count(), 2);
+        assert_eq!(chunk.texts.len(), 4);
+        let rows = read_chunk(&chunk).unwrap();
+        for (row, line) in rows.iter().zip(lines) {
+            assert_eq!(row.line, line);
+        }
+        assert_eq!(rows[1].report.as_ref().unwrap().mmsi, Mmsi(227006760));
+        assert!(rows[2].report.is_none());
+    }
+
+    #[test]
+    fn test_bad_row_count() {
+        use protobuf::Message;
+
+        // Nothing but `rows: u32::MAX`, which mustn't be allocated up front
+        let chunk = spec::ArchiveChunk::parse_from_bytes(b"\x10\xff\xff\xff\xff\x0f").unwrap();
+        assert_eq!(chunk.rows(), u32::MAX);
+        assert!(read_chunk(&chunk).is_err());
+
+        let mut writer = ArchiveWriter::new();
+        writer.push(0, "not nmea\n").unwrap();
+        writer.push(0, "at all\n").unwrap();
+        let mut chunk = writer.finish();
+        assert_eq!(read_chunk(&chunk).unwrap().len(), 2);
+        for rows in [1, 3] {
+            chunk.set_rows(rows);
+            assert!(read_chunk(&chunk).is_err(), "{rows}");
+        }
+    }
+}
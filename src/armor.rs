@@ -106,13 +106,11 @@ fn decode(c: char) -> Result<u8, &'static str> {
 
 fn encode(x: u8) -> Result<char, &'static str> {
     if x & 0xC0 != 0 {
-        return Err("encode - invalid char");
+        Err("encode - invalid char")
+    } else if x < 40 {
+        Ok((x + b'0').into())
     } else {
-        if x < 40 {
-            Ok((x + b'0').into())
-        } else {
-            Ok((x - 40 + b'`').into())
-        }
+        Ok((x - 40 + b'`').into())
     }
 }
 
@@ -194,6 +192,7 @@ mod tests {
         let sentence = crate::sentence::Nmea::parse(input).unwrap();
         let crate::sentence::Metadata {
             talker,
+            sentence_type,
             length,
             index,
             message_id,
@@ -202,13 +201,14 @@ mod tests {
             checksum,
         } = sentence.metadata;
 
-        let (data, drop_bits, garbage) = unpack(&*sentence.body, fill_bits.value()).unwrap();
+        let (data, drop_bits, garbage) = unpack(&sentence.body, fill_bits.value()).unwrap();
         let (packed, fill_bits) =
             pack(&data, drop_bits, garbage).unwrap_or_else(|e| panic!("{sentence} => {e}"));
 
         let new_sentence = crate::sentence::Nmea {
             metadata: crate::sentence::Metadata {
                 talker,
+                sentence_type,
                 length,
                 index,
                 message_id,
@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+
+use bit_struct::u3;
+
+use crate::sentence::Nmea;
+
+/// Fragments in progress are told apart by sentence type, channel and
+/// sequential message id, as the id alone only runs 0-9.
+type Key = (u8, u8, u8);
+
+#[derive(Debug)]
+struct Pending {
+    length: u8,
+    next: u8,
+    body: String,
+}
+
+/// Joins multipart `!xxVDM` sentences back into whole payloads, the inverse
+/// of [`crate::encoder::Encoder`]. A fragment arriving out of sequence
+/// drops whatever had been collected for its message id.
+#[derive(Debug, Default)]
+pub struct Assembler {
+    pending: HashMap<Key, Pending>,
+}
+
+impl Assembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a sentence, returning the unpacked payload once its last
+    /// fragment has arrived.
+    pub fn push(&mut self, sentence: &Nmea) -> Result<Option<(Vec<u8>, u3)>, &'static str> {
+        let m = &sentence.metadata;
+        if m.length <= 1 {
+            return unpack(&sentence.body, m.fill_bits).map(Some);
+        }
+        if m.index == 0 || m.index > m.length {
+            return Err("fragment index out of range");
+        }
+        let key = (u8::from(m.sentence_type), u8::from(m.channel), m.message_id);
+        if m.index == 1 {
+            self.pending.insert(
+                key,
+                Pending {
+                    length: m.length,
+                    next: 2,
+                    body: sentence.body.to_string(),
+                },
+            );
+        } else {
+            match self.pending.get_mut(&key) {
+                Some(p) if p.length == m.length && p.next == m.index => {
+                    p.body.push_str(&sentence.body);
+                    p.next += 1;
+                }
+                _ => {
+                    self.pending.remove(&key);
+                    return Err("fragment out of sequence");
+                }
+            }
+        }
+        if m.index < m.length {
+            return Ok(None);
+        }
+        let pending = self.pending.remove(&key).expect("inserted above");
+        unpack(&pending.body, m.fill_bits).map(Some)
+    }
+
+    /// Number of messages waiting for more fragments
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+fn unpack(body: &str, fill_bits: u3) -> Result<(Vec<u8>, u3), &'static str> {
+    let (data, drop_bits, _) = crate::armor::unpack(body, fill_bits.value())?;
+    Ok((data, drop_bits))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encoder::Encoder;
+    use crate::message::{Message, Payload};
+    use crate::mmsi::Mmsi;
+    use crate::sentence::{ChannelCode, TalkerID};
+
+    const AIS_SART: [&str; 2] = [
+        "!AIVDM,2,1,5,B,E1mg=5J1T4W0h97aRh6ba84<h2d;W:Te=eLvH50```q,0*46",
+        "!AIVDM,2,2,5,B,:D44QDlp0C1DU00,2*36",
+    ];
+
+    #[test]
+    fn test_single() {
+        let mut assembler = Assembler::new();
+        let nmea = Nmea::parse("!AIVDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*23").unwrap();
+        let (data, drop_bits) = assembler.push(&nmea).unwrap().unwrap();
+        let m = Message::from_bits(&data, drop_bits).unwrap();
+        assert_eq!(m.mmsi(), Some(Mmsi(227006760)));
+        assert_eq!(assembler.pending(), 0);
+    }
+
+    #[test]
+    fn test_multipart() {
+        let mut assembler = Assembler::new();
+        let first = Nmea::parse(AIS_SART[0]).unwrap();
+        let last = Nmea::parse(AIS_SART[1]).unwrap();
+        assert_eq!(assembler.push(&first), Ok(None));
+        assert_eq!(assembler.pending(), 1);
+        let (data, drop_bits) = assembler.push(&last).unwrap().unwrap();
+        assert!(matches!(
+            Message::from_bits(&data, drop_bits).unwrap(),
+            Message::AidToNavigationReport(_)
+        ));
+        assert_eq!(assembler.pending(), 0);
+
+        // Round trip through the encoder, interleaved with another message.
+        // Dropped bits come back as zeros.
+        let mut data = vec![0x5a; 60];
+        data[59] = 0x50;
+        let drop_bits = u3::new(4).unwrap();
+        let mut encoder = Encoder::new(TalkerID::AI, ChannelCode::A);
+        let a = encoder.encode_bits(&data, drop_bits).unwrap();
+        let b = encoder.encode_bits(&data, drop_bits).unwrap();
+        assert_eq!((a.len(), b.len()), (2, 2));
+        assert_eq!(assembler.push(&a[0]), Ok(None));
+        assert_eq!(assembler.push(&b[0]), Ok(None));
+        assert_eq!(assembler.push(&a[1]), Ok(Some((data.clone(), drop_bits))));
+        assert_eq!(assembler.push(&b[1]), Ok(Some((data, drop_bits))));
+    }
+
+    #[test]
+    fn test_out_of_sequence() {
+        let mut assembler = Assembler::new();
+        let last = Nmea::parse(AIS_SART[1]).unwrap();
+        assert!(assembler.push(&last).is_err());
+
+        // A repeated first fragment starts over
+        let first = Nmea::parse(AIS_SART[0]).unwrap();
+        assert_eq!(assembler.push(&first), Ok(None));
+        assert_eq!(assembler.push(&first), Ok(None));
+        assert!(assembler.push(&last).unwrap().is_some());
+    }
+}
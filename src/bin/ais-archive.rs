@@ -0,0 +1,83 @@
+use std::io::{BufRead, Write};
+use std::time::SystemTime;
+
+use ais_compact::archive::{ArchiveWriter, read_chunk};
+use ais_compact::proto::spec;
+use ais_compact::sentence::tag_block_time;
+use clap::Parser;
+use protobuf::Message;
+
+/// Stores NMEA lines from stdin as a column-oriented archive on stdout, or
+/// regenerates the lines with --extract.
+///
+/// Each line is stored with the time from the `c:` field of its NMEA tag
+/// block, or the last one seen. Input without tag blocks is taken to be a
+/// live feed, and stamped with the time each line is received.
+#[derive(Parser, Debug)]
+struct Args {
+    /// Read an archive from stdin and write the original lines
+    #[arg(long)]
+    extract: bool,
+    /// Lines per chunk. Larger chunks pack better.
+    #[arg(long, default_value = "4096")]
+    chunk_rows: usize,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    if args.extract {
+        extract()
+    } else {
+        archive(args.chunk_rows.max(1))
+    }
+}
+
+fn archive(chunk_rows: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let mut stdin = std::io::stdin().lock();
+    let mut stdout = std::io::stdout().lock();
+    let mut writer = ArchiveWriter::new();
+    let mut line = String::new();
+    let mut tag_time = None;
+    loop {
+        line.clear();
+        if stdin.read_line(&mut line)? == 0 {
+            break;
+        }
+        if let Some(time) = tag_block_time(&line) {
+            tag_time = Some(time);
+        }
+        let time = match tag_time {
+            Some(time) => time,
+            None => SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)?
+                .as_millis() as i64,
+        };
+        writer.push(time, &line)?;
+        if writer.len() >= chunk_rows {
+            writer
+                .finish()
+                .write_length_delimited_to_writer(&mut stdout)?;
+        }
+    }
+    if !writer.is_empty() {
+        writer
+            .finish()
+            .write_length_delimited_to_writer(&mut stdout)?;
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+fn extract() -> Result<(), Box<dyn std::error::Error>> {
+    let mut stdin = std::io::stdin().lock();
+    let mut reader = protobuf::CodedInputStream::from_buf_read(&mut stdin);
+    let mut stdout = std::io::stdout().lock();
+    while !reader.eof()? {
+        let chunk = reader.read_message::<spec::ArchiveChunk>()?;
+        for row in read_chunk(&chunk)? {
+            stdout.write_all(row.line.as_bytes())?;
+        }
+    }
+    stdout.flush()?;
+    Ok(())
+}
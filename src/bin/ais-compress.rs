@@ -1,40 +1,201 @@
 use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant, SystemTime};
 
-use clap::Parser;
+use ais_compact::block::{BlockWriter, Codec, Dictionary};
+use ais_compact::container::CountingWriter;
+use ais_compact::proto::spec;
+use clap::{Parser, Subcommand, ValueEnum};
 use protobuf::{CodedInputStream, Message};
 
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Compression {
+    /// Write and flush each message as it arrives
+    None,
+    Zstd,
+    Deflate,
+}
+
+impl From<Compression> for spec::Compression {
+    fn from(c: Compression) -> Self {
+        match c {
+            Compression::None => spec::Compression::UNCOMPRESSED,
+            Compression::Zstd => spec::Compression::ZSTD,
+            Compression::Deflate => spec::Compression::DEFLATE,
+        }
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Train a zstd dictionary from NMEA logs, for use with --dictionary
+    TrainDictionary {
+        /// Where to write the dictionary
+        #[arg(long)]
+        output: PathBuf,
+        /// Maximum dictionary size in bytes
+        #[arg(long, default_value = "16384")]
+        max_size: usize,
+        /// Log files to train on, one sentence per line
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+}
+
 #[derive(Parser, Debug)]
+#[command(args_conflicts_with_subcommands = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
     #[arg(long)]
     auth_code: Option<String>,
     #[arg(long, default_value = "512")]
     window_size: usize,
+    /// Don't record line endings and trailing whitespace, so every line is
+    /// reproduced with a bare "\n"
+    #[arg(long)]
+    normalise_line_endings: bool,
+    /// Leave out checksums which the decompressor can recompute, saving a
+    /// byte per message
+    #[arg(long)]
+    omit_checksum: bool,
+    /// Batch messages into blocks compressed with this algorithm
+    #[arg(long, value_enum, default_value_t = Compression::None)]
+    compression: Compression,
+    /// Maximum number of messages per block
+    #[arg(long, default_value = "64")]
+    block_messages: usize,
+    /// Maximum time in milliseconds a message waits in a block before it's
+    /// sent
+    #[arg(long, default_value = "1000")]
+    block_millis: u64,
+    /// zstd dictionary from `train-dictionary`. Combine with
+    /// `--block-messages 1` to keep per-message latency.
+    #[arg(long)]
+    dictionary: Option<PathBuf>,
+    /// Write a sync point after this many messages, making the output
+    /// seekable with `ais-decompress --from`
+    #[arg(long)]
+    sync_messages: Option<u64>,
+    /// Write a sync point after this many milliseconds, making the output
+    /// seekable with `ais-decompress --from`. Times come from the `c:` field
+    /// of each line's NMEA tag block, or the last one seen. Input without tag
+    /// blocks is taken to be a live feed, and stamped with the current time.
+    #[arg(long)]
+    sync_millis: Option<u64>,
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
 
-    let mut stdin = std::io::stdin().lock();
-    let mut stdout = std::io::stdout().lock();
+    if let Some(Command::TrainDictionary {
+        output,
+        max_size,
+        files,
+    }) = &args.command
+    {
+        return train_dictionary(output, *max_size, files);
+    }
 
-    header(&mut stdout, args.auth_code, args.window_size as i32)?;
+    let lines = spawn_reader();
+    let mut stdout = CountingWriter::new(std::io::stdout().lock());
 
-    // Buffers to be reused across loops
-    let mut line = String::new();
+    let dictionary = match &args.dictionary {
+        Some(path) => Some(Dictionary::new(&std::fs::read(path)?)),
+        None => None,
+    };
+    let codec = Codec::new(args.compression.into(), dictionary)?;
+    header(&mut stdout, args.auth_code, args.window_size as i32, &codec)?;
+
+    let mut blocks = (args.compression != Compression::None).then(|| {
+        BlockWriter::new(
+            codec,
+            args.block_messages.max(1),
+            Duration::from_millis(args.block_millis),
+        )
+    });
+
+    // Buffer to be reused across loops
     let mut roundtrip_buf = Vec::new();
 
     let mut prev = vec![String::new(); args.window_size];
     let mut pos = 0;
 
+    let seekable = args.sync_messages.is_some() || args.sync_millis.is_some();
+    let mut index = spec::Index::new();
+    let mut message_number = 0u64;
+    let mut last_sync: Option<(u64, i64)> = None;
+    let mut tag_time: Option<i64> = None;
+
     loop {
-        line.clear();
-        if stdin.read_line(&mut line)? == 0 {
-            break;
+        let line = match blocks.as_ref().and_then(BlockWriter::deadline) {
+            // Wake up in time to send a partially filled block
+            Some(deadline) => {
+                match lines.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                    Ok(line) => line?,
+                    Err(RecvTimeoutError::Timeout) => {
+                        write_block(&mut stdout, blocks.as_mut().unwrap())?;
+                        continue;
+                    }
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            None => match lines.recv() {
+                Ok(line) => line?,
+                Err(_) => break,
+            },
+        };
+        // Blank lines are kept, as empty raw messages, so the output matches
+        // the input byte for byte
+        let (line, trailing_whitespace, line_ending) = ais_compact::proto::split_line(&line);
+
+        // Receive time of the line, so a replayed log is indexed by when it
+        // was recorded
+        if let Some(time) = ais_compact::sentence::tag_block_time(line) {
+            tag_time = Some(time);
         }
-        let line = line.trim_end();
-        if line.is_empty() {
-            continue;
+        let time = match tag_time {
+            Some(time) => time,
+            // Nothing to go on, so assume a live feed
+            None if seekable => now_millis()?,
+            // Only needed for sync points
+            None => 0,
+        };
+
+        let sync_due = match last_sync {
+            None => true,
+            Some((n, at)) => {
+                args.sync_messages
+                    .is_some_and(|every| message_number - n >= every)
+                    || args
+                        .sync_millis
+                        .is_some_and(|every| time - at >= every as i64)
+            }
+        };
+        if seekable && sync_due {
+            // Start a new frame, so the sync point is at a known offset
+            if let Some(blocks) = blocks.as_mut() {
+                write_block(&mut stdout, blocks)?;
+            }
+            prev.fill(String::new());
+            pos = 0;
+
+            let mut sync = spec::SyncPoint::new();
+            sync.set_message_number(message_number);
+            sync.set_time(time);
+            let mut entry = spec::IndexEntry::new();
+            entry.set_offset(stdout.count());
+            entry.set_message_number(sync.message_number());
+            entry.set_time(sync.time());
+            index.entries.push(entry);
+
+            let mut message = spec::Message::new();
+            message.set_sync(sync);
+            send(&mut stdout, blocks.as_mut(), &message)?;
+            last_sync = Some((message_number, time));
         }
+        message_number += 1;
 
         let mut prev_ix: Option<usize> = None;
         if args.window_size > 0 {
@@ -42,7 +203,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             prev[pos] = line.to_owned();
 
             // Check if we've seen the message before. If we have, we can just send a 'repeat' marker
-            if let Some((ix, _)) = prev[..pos].iter().enumerate().find(|(_, s)| *s == line) {
+            if line.is_empty() {
+                // Would match slots which haven't been filled yet, and costs
+                // next to nothing anyway
+            } else if let Some((ix, _)) = prev[..pos].iter().enumerate().find(|(_, s)| *s == line) {
                 prev_ix = Some(pos - ix);
             } else if let Some((ix, _)) =
                 prev[pos + 1..].iter().enumerate().find(|(_, s)| *s == line)
@@ -54,50 +218,133 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             // post: 0 <= pos < window_size
         };
 
-        let (checksum_valid, checksum) = ais_compact::verify_checksum(line)?;
+        // Lines which aren't NMEA sentences at all have no checksum to verify
+        let checksum = ais_compact::verify_checksum(line).ok();
 
         // First, check if we've had a 'prev' match.
-        let message = if let Some(prev_ix) = prev_ix {
+        let mut message = if let Some(prev_ix) = prev_ix {
             eprintln!("Repeat match! -{prev_ix}");
             let mut m = ais_compact::proto::spec::Message::new();
             let mut r = ais_compact::proto::spec::Repeat::new();
             r.set_index(prev_ix as i32);
-            r.set_checksum(checksum.into());
+            r.set_checksum(checksum.map_or(0, |(_, c)| c).into());
             m.set_repeat(r);
             m
         }
-        // Then, check the checksum is valid. We'll be using it on the receiving side
-        // to check for errors, so if it's not already valid it'll have to be sent as
-        // a raw string.
-        else if checksum_valid {
+        // Otherwise encode the sentence. Sentences with an invalid checksum are
+        // still encoded, with a flag marking the checksum as known-bad so the
+        // receiving side reproduces the line exactly.
+        else {
+            if let Some((false, _)) = checksum {
+                eprintln!("Checksum check failed, encoding as known-bad: '{line}'");
+            }
             let mut message = line
-                .trim_end()
                 .parse::<ais_compact::proto::spec::Message>()
                 .unwrap_or_else(|e| match e {});
 
+            if args.omit_checksum && message.has_encoded() {
+                message.mut_encoded().omit_checksum()?;
+            }
+
             // Check round-trip succeeds - if not, send as raw string
-            if let Err(e) = check_roundtrip(&line, &message, &mut roundtrip_buf) {
+            if let Err(e) = check_roundtrip(line, &message, &mut roundtrip_buf) {
                 eprintln!("Error encoding, falling back to raw: {line}\n{e}");
                 // Convert the line into a raw message
                 message = ais_compact::proto::spec::Message::from(line.to_owned())
             };
 
             message
-        } else {
-            // Checksum check failed, send as raw string
-            eprintln!("Checksum check failed, sending raw string: '{line}'");
-            ais_compact::proto::spec::Message::from(line.to_owned())
         };
 
-        // FIXME: https://github.com/stepancheg/rust-protobuf/issues/541
-        //        https://github.com/JaimeValdemoros/ais-compact/pull/5
-        // Once we can flush the underlying writer, we should go back to having
-        // single CodedOutputStream instead of recreating it per loop
-        let mut writer = protobuf::CodedOutputStream::new(&mut stdout);
-        message.write_length_delimited_to(&mut writer)?;
-        writer.flush()?;
-        drop(writer);
-        stdout.flush()?
+        if !args.normalise_line_endings {
+            message.set_ending(trailing_whitespace, line_ending);
+        }
+
+        send(&mut stdout, blocks.as_mut(), &message)?;
+    }
+    if let Some(blocks) = blocks.as_mut() {
+        write_block(&mut stdout, blocks)?;
+    }
+    if seekable {
+        let offset = stdout.count();
+        let mut message = spec::Message::new();
+        message.set_index(index);
+        send(&mut stdout, blocks.as_mut(), &message)?;
+        if let Some(blocks) = blocks.as_mut() {
+            write_block(&mut stdout, blocks)?;
+        }
+        ais_compact::container::write_trailer(&mut stdout, offset)?;
+        stdout.flush()?;
+    }
+    Ok(())
+}
+
+fn now_millis() -> Result<i64, Box<dyn std::error::Error>> {
+    let elapsed = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
+    Ok(elapsed.as_millis() as i64)
+}
+
+/// Writes a message straight away, or adds it to the current block
+fn send(
+    stdout: &mut impl Write,
+    blocks: Option<&mut BlockWriter>,
+    message: &spec::Message,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(blocks) = blocks {
+        blocks.push(message)?;
+        if blocks.is_full() {
+            write_block(stdout, blocks)?;
+        }
+    } else {
+        write_flushed(stdout, message)?;
+    }
+    Ok(())
+}
+
+/// Reads stdin on a separate thread, so a pending block can be sent on time
+/// even while no new lines are arriving.
+fn spawn_reader() -> mpsc::Receiver<std::io::Result<String>> {
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut stdin = std::io::stdin().lock();
+        loop {
+            let mut line = String::new();
+            let result = match stdin.read_line(&mut line) {
+                Ok(0) => break,
+                Ok(_) => Ok(line),
+                Err(e) => Err(e),
+            };
+            let failed = result.is_err();
+            if tx.send(result).is_err() || failed {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+fn write_flushed(
+    stdout: &mut impl Write,
+    message: &impl Message,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // FIXME: https://github.com/stepancheg/rust-protobuf/issues/541
+    //        https://github.com/JaimeValdemoros/ais-compact/pull/5
+    // Once we can flush the underlying writer, we should go back to having
+    // single CodedOutputStream instead of recreating it per loop
+    let mut writer = protobuf::CodedOutputStream::new(stdout);
+    message.write_length_delimited_to(&mut writer)?;
+    writer.flush()?;
+    drop(writer);
+    stdout.flush()?;
+    Ok(())
+}
+
+fn write_block(
+    stdout: &mut impl Write,
+    blocks: &mut BlockWriter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(block) = blocks.finish()? {
+        write_flushed(stdout, &block)?;
     }
     Ok(())
 }
@@ -106,6 +353,7 @@ fn header(
     stdout: &mut impl Write,
     auth_code: Option<impl Into<String>>,
     window_size: i32,
+    codec: &Codec,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let mut header = ais_compact::proto::spec::Header::new();
     if let Some(auth_code) = auth_code {
@@ -115,6 +363,12 @@ fn header(
             .set_api_key(auth_code.into());
     }
     header.set_window_size(window_size);
+    if codec.compression() != spec::Compression::UNCOMPRESSED {
+        header.set_compression(codec.compression());
+    }
+    if let Some(dictionary) = codec.dictionary() {
+        header.set_dictionary_hash(dictionary.hash());
+    }
     let mut writer = protobuf::CodedOutputStream::new(stdout);
     header.write_length_delimited_to(&mut writer)?;
     writer.flush()?;
@@ -123,6 +377,26 @@ fn header(
     Ok(())
 }
 
+fn train_dictionary(
+    output: &PathBuf,
+    max_size: usize,
+    files: &[PathBuf],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut corpus = String::new();
+    for file in files {
+        corpus.push_str(&std::fs::read_to_string(file)?);
+        corpus.push('\n');
+    }
+    let dictionary = ais_compact::block::train_dictionary(corpus.split_inclusive('\n'), max_size)?;
+    std::fs::write(output, &dictionary)?;
+    eprintln!(
+        "Wrote {} byte dictionary {:016x}",
+        dictionary.len(),
+        Dictionary::new(&dictionary).hash()
+    );
+    Ok(())
+}
+
 fn check_roundtrip(
     line: &str,
     message: &ais_compact::proto::spec::Message,
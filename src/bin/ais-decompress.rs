@@ -1,6 +1,23 @@
-use std::io::{BufRead, Write};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::PathBuf;
 
-use clap::Parser;
+#[cfg(feature = "json")]
+use ais_compact::assembler::Assembler;
+use ais_compact::block::{Codec, Dictionary};
+use ais_compact::mmsi::{Mmsi, MmsiKind};
+use ais_compact::proto::spec;
+use ais_compact::window::Window;
+use clap::{Parser, ValueEnum};
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum Format {
+    /// The original sentences
+    Nmea,
+    /// One JSON object per line, with the sentence, its metadata and the
+    /// decoded message. Needs the `json` feature.
+    Json,
+}
 
 #[derive(Parser, Debug)]
 struct Args {
@@ -8,83 +25,274 @@ struct Args {
     auth_code: Option<String>,
     #[arg(long)]
     proxy_header: bool,
+    /// Recompute the checksum of decoded sentences rather than replaying
+    /// the transmitted one
+    #[arg(long)]
+    recompute_checksum: bool,
+    /// Write every line with a bare "\n", dropping any recorded line ending
+    /// and trailing whitespace
+    #[arg(long)]
+    normalise_line_endings: bool,
+    /// zstd dictionary, which must match the one the compressor used
+    #[arg(long)]
+    dictionary: Option<PathBuf>,
+    /// Read from a file instead of stdin
+    #[arg(long)]
+    input: Option<PathBuf>,
+    /// Start from the last sync point at or before this time, given as Unix
+    /// seconds or e.g. 2023-11-14T22:13:20Z. Needs a seekable --input.
+    #[arg(long, requires = "input", value_parser = ais_compact::container::parse_time)]
+    from: Option<i64>,
+    /// Start from this message number. Needs a seekable --input.
+    #[arg(long, requires = "input", conflicts_with = "from")]
+    from_message: Option<u64>,
+    #[arg(long, value_enum, default_value_t = Format::Nmea)]
+    format: Format,
+    /// Only output messages from this MMSI, which may be repeated. Lines
+    /// without a decoded message, such as the first fragments of multipart
+    /// messages, are left out. Needs --format json.
+    #[arg(long)]
+    mmsi: Vec<Mmsi>,
+    /// Only output messages from this kind of station, e.g. ship or
+    /// aid-to-navigation, which may be repeated. Needs --format json.
+    #[arg(long)]
+    station_kind: Vec<MmsiKind>,
+}
+
+impl Args {
+    fn filtered(&self) -> bool {
+        !self.mmsi.is_empty() || !self.station_kind.is_empty()
+    }
+
+    /// Whether a message from `mmsi` passes the filters
+    #[cfg(feature = "json")]
+    fn wants(&self, mmsi: Option<Mmsi>) -> bool {
+        if !self.filtered() {
+            return true;
+        }
+        mmsi.is_some_and(|mmsi| {
+            (self.mmsi.is_empty() || self.mmsi.contains(&mmsi))
+                && (self.station_kind.is_empty() || self.station_kind.contains(&mmsi.kind()))
+        })
+    }
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args = Args::parse();
+    if args.format == Format::Json && !cfg!(feature = "json") {
+        return Err(
+            anyhow::anyhow!("Built without the json feature, needed for --format json").into(),
+        );
+    }
+    if args.filtered() && args.format != Format::Json {
+        return Err(anyhow::anyhow!("--mmsi and --station-kind need --format json").into());
+    }
 
-    let mut stdin = std::io::stdin().lock();
-    if args.proxy_header {
-        let buf = stdin.fill_buf()?;
-        let (header, length) = proxy_header::ProxyHeader::parse(buf, Default::default())?;
-        eprintln!("{:#?}", header.proxied_address());
-        stdin.consume(length);
+    let mut input: Box<dyn BufRead> = match &args.input {
+        Some(path) => Box::new(BufReader::new(File::open(path)?)),
+        None => {
+            let mut stdin = std::io::stdin().lock();
+            if args.proxy_header {
+                let buf = stdin.fill_buf()?;
+                let (header, length) = proxy_header::ProxyHeader::parse(buf, Default::default())?;
+                eprintln!("{:#?}", header.proxied_address());
+                stdin.consume(length);
+            };
+            Box::new(stdin)
+        }
     };
 
-    let mut reader = protobuf::CodedInputStream::from_buf_read(&mut stdin);
+    let mut reader = protobuf::CodedInputStream::from_buf_read(&mut input);
     let mut stdout = std::io::stdout().lock();
 
-    // Buffer to avoid repeated allocations
-    let mut buf = Vec::new();
+    let header = validate_header(&mut reader, args.auth_code.as_deref())?;
+    let codec = codec(&header, args.dictionary.as_ref())?;
+    let mut decoder = Decoder::new(header.window_size().try_into().unwrap_or(0));
 
-    let window_size = validate_header(&mut reader, args.auth_code.as_ref().map(String::as_str))?;
-    let mut window: Vec<Option<ais_compact::proto::spec::Message>> = vec![None; window_size];
-    let mut pos = 0usize;
+    // Messages between the sync point and the one asked for
+    let mut skip = 0;
+    if args.from.is_some() || args.from_message.is_some() {
+        drop(reader);
+        let mut file = File::open(args.input.as_ref().unwrap())?;
+        let index = ais_compact::container::read_index(&mut file, &codec)?;
+        let entry = match (args.from, args.from_message) {
+            (Some(time), _) => index.find_time(time),
+            (_, Some(n)) => index.find_message(n),
+            (None, None) => unreachable!(),
+        }
+        .ok_or_else(|| anyhow::anyhow!("Index is empty"))?;
+        if let Some(n) = args.from_message {
+            skip = n - entry.message_number();
+        }
+        file.seek(SeekFrom::Start(entry.offset()))?;
+        input = Box::new(BufReader::new(file));
+        reader = protobuf::CodedInputStream::from_buf_read(&mut input);
+    }
 
-    while !reader.eof()? {
-        let mut message = reader.read_message::<ais_compact::proto::spec::Message>()?;
+    'frames: while !reader.eof()? {
+        let messages = if codec.compression() == spec::Compression::UNCOMPRESSED {
+            vec![reader.read_message::<spec::Message>()?]
+        } else {
+            codec.messages(&reader.read_message::<spec::Block>()?)?
+        };
+        for message in messages {
+            if message.has_index() {
+                // Only followed by the trailer
+                break 'frames;
+            }
+            if message.has_sync() {
+                decoder.window.reset();
+            } else if skip > 0 {
+                // Still needed in the window for later repeats
+                decoder.write(message, &args, &mut std::io::sink())?;
+                skip -= 1;
+            } else {
+                decoder.write(message, &args, &mut stdout)?;
+            }
+        }
+        stdout.flush()?;
+    }
+    Ok(())
+}
 
-        if message.has_repeat() {
-            assert!(window_size > 0);
-            let prev: usize = message.repeat().index().try_into().unwrap();
-            let checksum = message.repeat().checksum();
+/// Replays decoded messages, keeping the window which repeats refer back to
+struct Decoder {
+    window: Window,
+    // Joins multipart sentences for --format json
+    #[cfg(feature = "json")]
+    assembler: Assembler,
+    // Buffer to avoid repeated allocations
+    buf: Vec<u8>,
+}
 
-            assert!(prev < window_size);
-            let ix = if prev > pos {
-                window_size - prev + pos
-            } else {
-                pos - prev
-            };
+impl Decoder {
+    fn new(window_size: usize) -> Self {
+        Decoder {
+            window: Window::new(window_size),
+            #[cfg(feature = "json")]
+            assembler: Assembler::new(),
+            buf: Vec::new(),
+        }
+    }
 
-            // Correctness: 1 <= ix < message_size
-            let prev_message = window[ix].as_ref().cloned().unwrap();
-            let prev_checksum = ais_compact::verify_checksum(&prev_message.try_to_string()?)?.1;
-            if u32::from(prev_checksum) != checksum {
-                return Err(
-                    anyhow::anyhow!("Mismatched checksum: {prev_checksum} != {checksum}").into(),
-                );
-            };
-            message = prev_message;
+    fn write(
+        &mut self,
+        mut message: spec::Message,
+        args: &Args,
+        stdout: &mut impl Write,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if message.has_repeat() {
+            message = self.window.resolve(&message)?.1;
         }
 
         if message.has_encoded() {
-            buf.clear();
-            message.try_write(&mut buf)?;
-            let s = std::str::from_utf8(&buf)?;
-            if !ais_compact::verify_checksum(s)?.0 {
+            self.buf.clear();
+            message.try_write(&mut self.buf)?;
+            let s = std::str::from_utf8(&self.buf)?;
+            let valid = ais_compact::verify_checksum(s)?.0;
+            if !valid && !message.encoded().checksum_known_bad() {
                 return Err(anyhow::anyhow!("Invalid checksum").into());
             }
+            if valid && message.encoded().checksum_known_bad() {
+                return Err(anyhow::anyhow!("Checksum flagged as bad but valid").into());
+            }
+        }
+
+        if args.format == Format::Json {
+            self.write_json(&message, args, &mut *stdout)?;
+        } else {
+            message.try_write_with(&mut *stdout, args.recompute_checksum)?;
+            if args.normalise_line_endings {
+                stdout.write_all(b"\n")?;
+            } else {
+                message.write_ending(&mut *stdout)?;
+            }
         }
 
-        message.try_write(&mut stdout)?;
+        self.window.push(message);
+        Ok(())
+    }
+
+    /// Writes the sentence, its metadata and, once the last fragment of a
+    /// multipart message has arrived, the decoded message. Anything which
+    /// can't be decoded is reported in `error` rather than stopping the
+    /// stream, and anything wrong with a decoded message in `issues`.
+    #[cfg(feature = "json")]
+    fn write_json(
+        &mut self,
+        message: &spec::Message,
+        args: &Args,
+        stdout: &mut impl Write,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        use ais_compact::message::Message;
+        use ais_compact::proto::metadata::MetadataFields;
+        use ais_compact::sentence::Nmea;
 
-        if window_size > 0 {
-            // Pre: 0 <= pos < window_size
-            window[pos] = Some(message);
-            pos = (pos + 1) % window_size;
-            // post: 0 <= pos < window_size
+        #[derive(serde::Serialize)]
+        struct Line<'a> {
+            sentence: &'a str,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            metadata: Option<MetadataFields>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            message: Option<Message>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            error: Option<String>,
+            // Why a payload decoded as Unknown, or fields out of range
+            #[serde(skip_serializing_if = "Vec::is_empty")]
+            issues: Vec<String>,
         }
 
+        self.buf.clear();
+        message.try_write_with(&mut self.buf, args.recompute_checksum)?;
+        let mut line = Line {
+            sentence: std::str::from_utf8(&self.buf)?,
+            metadata: None,
+            message: None,
+            error: None,
+            issues: Vec::new(),
+        };
+        if message.has_encoded() {
+            let encoded = message.encoded();
+            line.metadata = Some(encoded.metadata_fields()?);
+            let nmea = Nmea::try_from(encoded)?;
+            match self.assembler.push(&nmea) {
+                Ok(Some((data, drop_bits))) => {
+                    match Message::from_bits_validated(&data, drop_bits) {
+                        Ok((decoded, issues)) => {
+                            line.message = Some(decoded);
+                            line.issues = issues.iter().map(ToString::to_string).collect();
+                        }
+                        Err(e) => line.error = Some(e.to_string()),
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => line.error = Some(e.to_owned()),
+            }
+        }
+        if !args.wants(line.message.as_ref().and_then(Message::mmsi)) {
+            return Ok(());
+        }
+        serde_json::to_writer(&mut *stdout, &line)?;
         stdout.write_all(b"\n")?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "json"))]
+    fn write_json(
+        &mut self,
+        _message: &spec::Message,
+        _args: &Args,
+        _stdout: &mut impl Write,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        unreachable!("rejected in main")
     }
-    Ok(())
 }
 
 fn validate_header(
     reader: &mut protobuf::CodedInputStream,
     auth_code: Option<&str>,
-) -> anyhow::Result<usize> {
-    let header = reader.read_message::<ais_compact::proto::spec::Header>()?;
+) -> anyhow::Result<spec::Header> {
+    let header = reader.read_message::<spec::Header>()?;
     if let Some(auth_code) = auth_code {
         if !header.auth.has_api_key() {
             anyhow::bail!("No API key provided");
@@ -97,9 +305,31 @@ fn validate_header(
             );
         }
     }
-    Ok(header
-        .window_size
-        .unwrap_or_default()
-        .try_into()
-        .unwrap_or(0))
+    Ok(header)
+}
+
+fn codec(header: &spec::Header, dictionary: Option<&PathBuf>) -> anyhow::Result<Codec> {
+    let dictionary = match (header.has_dictionary_hash(), dictionary) {
+        (false, None) => None,
+        (false, Some(_)) => {
+            eprintln!("Stream doesn't use a dictionary, ignoring --dictionary");
+            None
+        }
+        (true, None) => anyhow::bail!(
+            "Stream needs dictionary {:016x}, pass it with --dictionary",
+            header.dictionary_hash()
+        ),
+        (true, Some(path)) => {
+            let dictionary = Dictionary::new(&std::fs::read(path)?);
+            if dictionary.hash() != header.dictionary_hash() {
+                anyhow::bail!(
+                    "Dictionary mismatch: {:016x} != {:016x}",
+                    dictionary.hash(),
+                    header.dictionary_hash()
+                );
+            }
+            Some(dictionary)
+        }
+    };
+    Codec::new(header.compression(), dictionary)
 }
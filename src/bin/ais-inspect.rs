@@ -0,0 +1,261 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+
+use ais_compact::block::{Codec, Dictionary};
+use ais_compact::proto::spec;
+use ais_compact::window::Window;
+use clap::Parser;
+use protobuf::Message;
+
+/// Prints the frames of a compressed stream, and how well each kind of
+/// message compressed.
+#[derive(Parser, Debug)]
+struct Args {
+    /// Compressed stream to read, stdin if not given
+    input: Option<PathBuf>,
+    /// zstd dictionary the stream was compressed with
+    #[arg(long)]
+    dictionary: Option<PathBuf>,
+    /// Only print the summary
+    #[arg(long)]
+    summary: bool,
+}
+
+#[derive(Debug, Default)]
+struct Stats {
+    count: u64,
+    /// Serialized size, before any block compression
+    encoded: u64,
+    /// Size of the reconstructed lines, including line endings
+    nmea: u64,
+}
+
+impl Stats {
+    fn add(&mut self, encoded: u64, nmea: u64) {
+        self.count += 1;
+        self.encoded += encoded;
+        self.nmea += nmea;
+    }
+
+    fn ratio(&self) -> f64 {
+        self.nmea as f64 / self.encoded.max(1) as f64
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+
+    let mut input: Box<dyn BufRead> = match &args.input {
+        Some(path) => Box::new(BufReader::new(File::open(path)?)),
+        None => Box::new(std::io::stdin().lock()),
+    };
+    let mut reader = protobuf::CodedInputStream::from_buf_read(&mut input);
+
+    let mut header = reader.read_message::<spec::Header>()?;
+    let header_size = reader.pos();
+    if header.auth.has_api_key() {
+        header
+            .auth
+            .mut_or_insert_default()
+            .set_api_key("<redacted>".into());
+    }
+    println!("header ({header_size} bytes)");
+    print!("{}", protobuf::text_format::print_to_string_pretty(&header));
+    println!();
+
+    let dictionary = match &args.dictionary {
+        Some(path) => Some(Dictionary::new(&std::fs::read(path)?)),
+        None => None,
+    };
+    if let Some(dictionary) = &dictionary
+        && dictionary.hash() != header.dictionary_hash()
+    {
+        eprintln!("Dictionary {:016x} doesn't match header", dictionary.hash());
+    }
+    let codec = Codec::new(header.compression(), dictionary)?;
+    let mut window = Window::new(header.window_size().try_into().unwrap_or(0));
+
+    let mut stats: BTreeMap<&'static str, Stats> = BTreeMap::new();
+    let mut blocks = Stats::default();
+
+    'frames: while !reader.eof()? {
+        let start = reader.pos();
+        let messages = if codec.compression() == spec::Compression::UNCOMPRESSED {
+            vec![reader.read_message::<spec::Message>()?]
+        } else {
+            let block = reader.read_message::<spec::Block>()?;
+            let messages = codec.messages(&block)?;
+            let compressed = reader.pos() - start;
+            let uncompressed: u64 = messages.iter().map(frame_size).sum();
+            blocks.add(compressed, uncompressed);
+            if !args.summary {
+                println!(
+                    "block @{start}: {} messages, {compressed} bytes ({uncompressed} uncompressed)",
+                    messages.len()
+                );
+            }
+            messages
+        };
+
+        for message in messages {
+            let size = frame_size(&message);
+            let variant = variant(&message);
+            let mut out = Vec::new();
+            if !args.summary {
+                print!("{variant} {size} bytes");
+            }
+
+            let decoded = match variant {
+                "sync" | "index" => None,
+                "repeat" => {
+                    let index = message.repeat().index();
+                    match window.resolve(&message) {
+                        Ok((target, resolved)) => {
+                            out.push(format!("index -{index} -> #{target}"));
+                            Some(resolved)
+                        }
+                        Err(e) => {
+                            out.push(format!("index -{index}, error: {e}"));
+                            // Keep the window in step with the compressor
+                            window.push(spec::Message::from(String::new()));
+                            None
+                        }
+                    }
+                }
+                "empty" | "unknown" => {
+                    out.push("undecodable: no known message variant set".to_owned());
+                    None
+                }
+                _ => Some(message.clone()),
+            };
+
+            if message.has_encoded() {
+                let e = message.encoded();
+                let form = if e.has_packed_metadata() {
+                    "packed".to_owned()
+                } else {
+                    ais_compact::proto::metadata::version(e.metadata())
+                        .map_or_else(|e| format!("{e}"), |v| format!("v{v}"))
+                };
+                match e.metadata_fields() {
+                    Ok(fields) => out.push(format!("metadata ({form}): {fields:?}")),
+                    Err(err) => out.push(format!("metadata ({form}): error: {err}")),
+                }
+                out.push(format!("payload: {} bytes", e.body().len()));
+            }
+            if message.has_sync() {
+                let sync = message.sync();
+                out.push(format!(
+                    "message_number: {}, time: {}",
+                    sync.message_number(),
+                    sync.time()
+                ));
+                window.reset();
+            }
+            if message.has_index() {
+                out.push(format!("{} entries", message.index().entries.len()));
+            }
+
+            let line = match &decoded {
+                Some(decoded) => {
+                    let mut line = Vec::new();
+                    match decoded
+                        .try_write(&mut line)
+                        .and_then(|_| Ok(decoded.write_ending(&mut line)?))
+                    {
+                        Ok(()) => Some(String::from_utf8_lossy(&line).into_owned()),
+                        Err(e) => {
+                            out.push(format!("error: {e}"));
+                            None
+                        }
+                    }
+                }
+                None => None,
+            };
+            let nmea = line.as_ref().map_or(0, |l| l.len() as u64);
+            stats.entry(variant).or_default().add(size, nmea);
+
+            if let Some(decoded) = decoded {
+                let number = window.push(decoded);
+                if !args.summary {
+                    print!(" #{number}");
+                }
+            }
+            if !args.summary {
+                println!();
+                for detail in out {
+                    println!("    {detail}");
+                }
+                if let Some(line) = line {
+                    println!("    {line:?}");
+                }
+            }
+            if variant == "index" {
+                // Only followed by the trailer
+                break 'frames;
+            }
+        }
+    }
+
+    let total_size = reader.pos();
+    let total = stats.values().fold(Stats::default(), |mut total, s| {
+        total.count += s.count;
+        total.encoded += s.encoded;
+        total.nmea += s.nmea;
+        total
+    });
+
+    println!();
+    println!(
+        "{:<10} {:>10} {:>12} {:>12} {:>7}",
+        "variant", "count", "encoded", "nmea", "ratio"
+    );
+    for (variant, s) in stats
+        .iter()
+        .map(|(v, s)| (*v, s))
+        .chain([("total", &total)])
+    {
+        println!(
+            "{:<10} {:>10} {:>12} {:>12} {:>7.2}",
+            variant,
+            s.count,
+            s.encoded,
+            s.nmea,
+            s.ratio()
+        );
+    }
+    if blocks.count > 0 {
+        println!(
+            "{} blocks, {} bytes from {} ({:.2})",
+            blocks.count,
+            blocks.encoded,
+            blocks.nmea,
+            blocks.nmea as f64 / blocks.encoded.max(1) as f64
+        );
+    }
+    println!(
+        "stream: {total_size} bytes including header, {:.2} overall",
+        total.nmea as f64 / total_size.max(1) as f64
+    );
+    Ok(())
+}
+
+fn frame_size(message: &spec::Message) -> u64 {
+    let size = message.compute_size();
+    size + protobuf::rt::compute_raw_varint64_size(size)
+}
+
+fn variant(message: &spec::Message) -> &'static str {
+    match &message.types {
+        Some(spec::message::Types::Raw(_)) => "raw",
+        Some(spec::message::Types::Encoded(_)) => "encoded",
+        Some(spec::message::Types::Repeat(_)) => "repeat",
+        Some(spec::message::Types::Generic(_)) => "generic",
+        Some(spec::message::Types::Sync(_)) => "sync",
+        Some(spec::message::Types::Index(_)) => "index",
+        Some(_) => "unknown",
+        None => "empty",
+    }
+}
@@ -16,7 +16,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         match sentence::Nmea::parse(line.trim_end()) {
             Ok(mut sentence) => {
                 let (data, drop_bits, garbage) =
-                    armor::unpack(&*sentence.body, sentence.metadata.fill_bits.value()).unwrap();
+                    armor::unpack(&sentence.body, sentence.metadata.fill_bits.value()).unwrap();
                 let Ok((packed, fill_bits)) = armor::pack(&data, drop_bits, garbage)
                     .inspect_err(|e| eprintln!("{sentence} => {e}"))
                 else {
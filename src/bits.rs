@@ -0,0 +1,320 @@
+use std::fmt;
+
+use bit_struct::u3;
+
+/// Returned when a read would go past the end of the payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overrun {
+    /// Bit offset the read started at
+    pub offset: usize,
+    /// Number of bits requested
+    pub requested: usize,
+    /// Total number of bits in the payload
+    pub len: usize,
+}
+
+impl fmt::Display for Overrun {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            fmt,
+            "read of {} bits at offset {} overruns {}-bit payload",
+            self.requested, self.offset, self.len
+        )
+    }
+}
+
+impl std::error::Error for Overrun {}
+
+/// For `#[serde(serialize_with)]` on the `drop_bits` of raw payloads
+#[cfg(feature = "serde")]
+pub(crate) fn serialize_u3<S: serde::Serializer>(x: &u3, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_u8(x.value())
+}
+
+/// Cursor over the bytes produced by [`crate::armor::unpack`], reading
+/// big-endian fields of arbitrary width.
+#[derive(Debug, Clone)]
+pub struct BitReader<'a> {
+    data: &'a [u8],
+    len: usize,
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    /// `drop_bits` is the number of unused bits at the end of the last byte,
+    /// as returned alongside the data by `armor::unpack`.
+    pub fn new(data: &'a [u8], drop_bits: u3) -> Self {
+        let len = (data.len() * 8).saturating_sub(drop_bits.value().into());
+        BitReader { data, len, pos: 0 }
+    }
+
+    /// Total number of bits in the payload
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Current bit offset from the start of the payload
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.len - self.pos
+    }
+
+    fn check(&self, bits: usize) -> Result<(), Overrun> {
+        if bits > self.remaining() {
+            return Err(Overrun {
+                offset: self.pos,
+                requested: bits,
+                len: self.len,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn skip(&mut self, bits: usize) -> Result<(), Overrun> {
+        self.check(bits)?;
+        self.pos += bits;
+        Ok(())
+    }
+
+    /// Reads an unsigned field of up to 64 bits.
+    pub fn read_u(&mut self, bits: usize) -> Result<u64, Overrun> {
+        assert!(bits <= 64, "field wider than 64 bits");
+        self.check(bits)?;
+        let mut acc = 0u64;
+        let mut left = bits;
+        while left > 0 {
+            // Take as many bits as we can from the current byte
+            let byte = self.data[self.pos / 8];
+            let offset = self.pos % 8;
+            let take = left.min(8 - offset);
+            let chunk = (byte << offset) >> (8 - take);
+            acc = (acc << take) | u64::from(chunk);
+            self.pos += take;
+            left -= take;
+        }
+        Ok(acc)
+    }
+
+    /// Reads a two's complement signed field of up to 64 bits.
+    pub fn read_i(&mut self, bits: usize) -> Result<i64, Overrun> {
+        let raw = self.read_u(bits)?;
+        if bits == 0 {
+            return Ok(0);
+        }
+        // Shift the sign bit up to bit 63 and back down to sign-extend
+        let shift = 64 - bits as u32;
+        Ok(((raw << shift) as i64) >> shift)
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, Overrun> {
+        Ok(self.read_u(1)? == 1)
+    }
+
+    /// Reads `chars` six-bit ASCII characters. Padding ('@') is returned
+    /// as-is so the caller can decide how to trim it.
+    pub fn read_str(&mut self, chars: usize) -> Result<String, Overrun> {
+        self.check(chars * 6)?;
+        let mut out = String::with_capacity(chars);
+        for _ in 0..chars {
+            out.push(sixbit_to_char(self.read_u(6)? as u8));
+        }
+        Ok(out)
+    }
+
+    /// Copies out the next `bits` bits, left-aligned into bytes. The second
+    /// value is the number of unused bits at the end of the last byte, so the
+    /// result can be fed back into [`BitReader::new`].
+    pub fn read_bits(&mut self, bits: usize) -> Result<(Vec<u8>, u3), Overrun> {
+        self.check(bits)?;
+        let mut out = BitWriter::new();
+        let mut left = bits;
+        while left > 0 {
+            let take = left.min(8);
+            out.write_u(take, self.read_u(take)?);
+            left -= take;
+        }
+        Ok(out.finish())
+    }
+}
+
+/// Builds a payload bit by bit, the inverse of [`BitReader`].
+#[derive(Debug, Clone, Default)]
+pub struct BitWriter {
+    data: Vec<u8>,
+    len: usize,
+}
+
+impl BitWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of bits written so far
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Writes the lowest `bits` bits of `value`. Higher bits are ignored.
+    pub fn write_u(&mut self, bits: usize, value: u64) {
+        assert!(bits <= 64, "field wider than 64 bits");
+        let mut left = bits;
+        while left > 0 {
+            let offset = self.len % 8;
+            if offset == 0 {
+                self.data.push(0);
+            }
+            let take = left.min(8 - offset);
+            let chunk = ((value >> (left - take)) & ((1 << take) - 1)) as u8;
+            *self.data.last_mut().unwrap() |= chunk << (8 - offset - take);
+            self.len += take;
+            left -= take;
+        }
+    }
+
+    /// Writes `value` as a `bits`-wide two's complement field.
+    pub fn write_i(&mut self, bits: usize, value: i64) {
+        self.write_u(bits, value as u64)
+    }
+
+    pub fn write_bool(&mut self, value: bool) {
+        self.write_u(1, value.into())
+    }
+
+    /// Writes `s` as exactly `chars` six-bit characters, truncating or
+    /// padding with '@'. Lowercase letters are upper-cased and anything else
+    /// outside the six-bit table is written as '?'.
+    pub fn write_str(&mut self, chars: usize, s: &str) {
+        let mut iter = s.chars();
+        for _ in 0..chars {
+            let c = iter.next().unwrap_or('@');
+            self.write_u(6, char_to_sixbit(c).into());
+        }
+    }
+
+    /// Appends `bits` bits taken from the front of `data`.
+    pub fn write_bits(&mut self, data: &[u8], bits: usize) {
+        let mut reader = BitReader {
+            data,
+            len: bits.min(data.len() * 8),
+            pos: 0,
+        };
+        while reader.remaining() > 0 {
+            let take = reader.remaining().min(8);
+            let chunk = reader.read_u(take).expect("bounds checked");
+            self.write_u(take, chunk);
+        }
+    }
+
+    /// Returns the bytes written and the number of unused bits at the end of
+    /// the last byte, ready for [`crate::armor::pack`].
+    pub fn finish(self) -> (Vec<u8>, u3) {
+        let drop_bits = (8 - self.len % 8) % 8;
+        (self.data, u3::new(drop_bits as u8).expect("drop_bits < 8"))
+    }
+}
+
+fn sixbit_to_char(x: u8) -> char {
+    // 0..=31 map onto '@'..='_', 32..=63 onto ' '..='?'
+    if x < 32 { (x + 64).into() } else { x.into() }
+}
+
+fn char_to_sixbit(c: char) -> u8 {
+    match c.to_ascii_uppercase() {
+        c @ ' '..='?' => c as u8,
+        c @ '@'..='_' => c as u8 - 64,
+        _ => b'?',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unpacked(body: &str, fill_bits: u8) -> (Vec<u8>, u3) {
+        let (data, drop_bits, _) = crate::armor::unpack(body, fill_bits).unwrap();
+        (data, drop_bits)
+    }
+
+    #[test]
+    fn test_read_position_report() {
+        let (data, drop_bits) = unpacked("13HOI:0P0000VOHLCnHQKwvL05Ip", 0);
+        let mut r = BitReader::new(&data, drop_bits);
+        assert_eq!(r.len(), 168);
+        assert_eq!(r.read_u(6).unwrap(), 1);
+        assert_eq!(r.read_u(2).unwrap(), 0);
+        assert_eq!(r.read_u(30).unwrap(), 227006760);
+        assert_eq!(r.read_u(4).unwrap(), 0);
+        assert_eq!(r.read_i(8).unwrap(), -128);
+        assert_eq!(r.read_u(10).unwrap(), 0);
+        assert!(!r.read_bool().unwrap());
+        assert_eq!(r.read_i(28).unwrap(), 78828);
+        assert_eq!(r.read_i(27).unwrap(), 29685346);
+        assert_eq!(r.position(), 116);
+    }
+
+    #[test]
+    fn test_overrun() {
+        let (data, drop_bits) = unpacked("13HOI:0P0000VOHLCnHQKwvL05Ip", 0);
+        let mut r = BitReader::new(&data, drop_bits);
+        r.skip(160).unwrap();
+        assert_eq!(
+            r.read_u(9),
+            Err(Overrun {
+                offset: 160,
+                requested: 9,
+                len: 168
+            })
+        );
+        // A failed read doesn't move the cursor
+        assert_eq!(r.position(), 160);
+        assert!(r.read_u(8).is_ok());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut w = BitWriter::new();
+        w.write_u(6, 5);
+        w.write_i(27, -12345678);
+        w.write_bool(true);
+        w.write_str(7, "ab-1");
+        w.write_u(3, 0b101);
+        assert_eq!(w.len(), 6 + 27 + 1 + 42 + 3);
+
+        let (data, drop_bits) = w.finish();
+        assert_eq!(drop_bits.value(), 1);
+        let mut r = BitReader::new(&data, drop_bits);
+        assert_eq!(r.read_u(6).unwrap(), 5);
+        assert_eq!(r.read_i(27).unwrap(), -12345678);
+        assert!(r.read_bool().unwrap());
+        assert_eq!(r.read_str(7).unwrap(), "AB-1@@@");
+        assert_eq!(r.read_u(3).unwrap(), 0b101);
+        assert_eq!(r.remaining(), 0);
+    }
+
+    #[test]
+    fn test_read_bits() {
+        let (data, drop_bits) = unpacked("13HOI:0P0000VOHLCnHQKwvL05Ip", 0);
+        let mut r = BitReader::new(&data, drop_bits);
+        r.skip(38).unwrap();
+        let (bits, drop) = r.read_bits(19).unwrap();
+        let mut w = BitWriter::new();
+        w.write_bits(&bits, 19);
+        assert_eq!(w.finish(), (bits.clone(), drop));
+
+        let mut sub = BitReader::new(&bits, drop);
+        assert_eq!(sub.len(), 19);
+        assert_eq!(sub.read_u(4).unwrap(), 0);
+        assert_eq!(sub.read_i(8).unwrap(), -128);
+    }
+}
@@ -0,0 +1,293 @@
+//! Block framing, where the compressor batches several messages and runs
+//! them through a general-purpose compressor together. This trades latency
+//! for bandwidth compared to flushing every message on its own.
+
+use std::fmt;
+use std::io::{Read, Write};
+use std::time::{Duration, Instant};
+
+use protobuf::{CodedInputStream, Message as _};
+use zstd::dict::{DecoderDictionary, EncoderDictionary};
+
+use crate::proto::spec;
+
+/// A zstd dictionary, which gives the compressor context for short blocks.
+/// Both sides must load the same one, identified in the header by its hash.
+pub struct Dictionary {
+    hash: u64,
+    encoder: EncoderDictionary<'static>,
+    decoder: DecoderDictionary<'static>,
+}
+
+impl Dictionary {
+    pub fn new(data: &[u8]) -> Self {
+        Dictionary {
+            hash: dictionary_hash(data),
+            encoder: EncoderDictionary::copy(data, zstd::DEFAULT_COMPRESSION_LEVEL),
+            decoder: DecoderDictionary::copy(data),
+        }
+    }
+
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+}
+
+impl fmt::Debug for Dictionary {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "Dictionary({:016x})", self.hash)
+    }
+}
+
+/// 64-bit FNV-1a, which unlike `DefaultHasher` is stable across Rust
+/// versions.
+fn dictionary_hash(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Trains a dictionary on the encoded form of each line, so it matches
+/// what the compressor actually sees.
+pub fn train_dictionary<'a>(
+    lines: impl IntoIterator<Item = &'a str>,
+    max_size: usize,
+) -> anyhow::Result<Vec<u8>> {
+    let mut samples = Vec::new();
+    for line in lines {
+        let (line, _, _) = crate::proto::split_line(line);
+        if line.is_empty() {
+            continue;
+        }
+        let message = line.parse::<spec::Message>().unwrap_or_else(|e| match e {});
+        samples.push(message.write_length_delimited_to_bytes()?);
+    }
+    Ok(zstd::dict::from_samples(&samples, max_size)?)
+}
+
+/// The compression negotiated in the header, along with its dictionary if
+/// any.
+#[derive(Debug)]
+pub struct Codec {
+    compression: spec::Compression,
+    dictionary: Option<Dictionary>,
+}
+
+impl Codec {
+    pub fn new(
+        compression: spec::Compression,
+        dictionary: Option<Dictionary>,
+    ) -> anyhow::Result<Self> {
+        if dictionary.is_some() && compression != spec::Compression::ZSTD {
+            anyhow::bail!("dictionaries are only supported with zstd");
+        }
+        Ok(Codec {
+            compression,
+            dictionary,
+        })
+    }
+
+    pub fn compression(&self) -> spec::Compression {
+        self.compression
+    }
+
+    pub fn dictionary(&self) -> Option<&Dictionary> {
+        self.dictionary.as_ref()
+    }
+
+    pub fn compress(&self, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+        Ok(match (self.compression, &self.dictionary) {
+            (spec::Compression::UNCOMPRESSED, _) => data.to_vec(),
+            (spec::Compression::ZSTD, None) => {
+                zstd::bulk::compress(data, zstd::DEFAULT_COMPRESSION_LEVEL)?
+            }
+            (spec::Compression::ZSTD, Some(dictionary)) => {
+                let mut encoder = zstd::stream::Encoder::with_prepared_dictionary(
+                    Vec::new(),
+                    &dictionary.encoder,
+                )?;
+                // The header already identifies the dictionary
+                encoder.include_dictid(false)?;
+                encoder.write_all(data)?;
+                encoder.finish()?
+            }
+            (spec::Compression::DEFLATE, _) => {
+                let mut encoder =
+                    flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(data)?;
+                encoder.finish()?
+            }
+        })
+    }
+
+    pub fn decompress(&self, data: &[u8]) -> anyhow::Result<Vec<u8>> {
+        Ok(match (self.compression, &self.dictionary) {
+            (spec::Compression::UNCOMPRESSED, _) => data.to_vec(),
+            (spec::Compression::ZSTD, None) => zstd::stream::decode_all(data)?,
+            (spec::Compression::ZSTD, Some(dictionary)) => {
+                let mut out = Vec::new();
+                zstd::stream::Decoder::with_prepared_dictionary(data, &dictionary.decoder)?
+                    .read_to_end(&mut out)?;
+                out
+            }
+            (spec::Compression::DEFLATE, _) => {
+                let mut out = Vec::new();
+                flate2::read::DeflateDecoder::new(data).read_to_end(&mut out)?;
+                out
+            }
+        })
+    }
+
+    /// Decompresses a block back into the messages it was built from.
+    pub fn messages(&self, block: &spec::Block) -> anyhow::Result<Vec<spec::Message>> {
+        let data = self.decompress(block.data())?;
+        let mut input = CodedInputStream::from_bytes(&data);
+        let mut messages = Vec::new();
+        while !input.eof()? {
+            messages.push(input.read_message()?);
+        }
+        Ok(messages)
+    }
+}
+
+/// Collects messages until either `max_messages` have been added or
+/// `max_delay` has passed since the first one.
+#[derive(Debug)]
+pub struct BlockWriter {
+    codec: Codec,
+    max_messages: usize,
+    max_delay: Duration,
+    buf: Vec<u8>,
+    count: usize,
+    started: Option<Instant>,
+}
+
+impl BlockWriter {
+    pub fn new(codec: Codec, max_messages: usize, max_delay: Duration) -> Self {
+        BlockWriter {
+            codec,
+            max_messages,
+            max_delay,
+            buf: Vec::new(),
+            count: 0,
+            started: None,
+        }
+    }
+
+    pub fn push(&mut self, message: &spec::Message) -> protobuf::Result<()> {
+        message.write_length_delimited_to_vec(&mut self.buf)?;
+        self.count += 1;
+        self.started.get_or_insert_with(Instant::now);
+        Ok(())
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.count >= self.max_messages
+    }
+
+    /// When the pending messages must be flushed, if there are any
+    pub fn deadline(&self) -> Option<Instant> {
+        self.started.map(|started| started + self.max_delay)
+    }
+
+    /// Compresses the pending messages into a block, leaving the writer
+    /// empty. Returns `None` if nothing was pushed since the last block.
+    pub fn finish(&mut self) -> anyhow::Result<Option<spec::Block>> {
+        if self.count == 0 {
+            return Ok(None);
+        }
+        let mut block = spec::Block::new();
+        block.set_data(self.codec.compress(&self.buf)?);
+        self.buf.clear();
+        self.count = 0;
+        self.started = None;
+        Ok(Some(block))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mmsi::Mmsi;
+
+    #[test]
+    fn test_round_trip() {
+        let lines = [
+            "!AIVDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*23",
+            "$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A",
+            "not nmea at all",
+        ];
+        let expected: Vec<spec::Message> = lines.iter().map(|l| l.parse().unwrap()).collect();
+
+        for compression in [
+            spec::Compression::UNCOMPRESSED,
+            spec::Compression::ZSTD,
+            spec::Compression::DEFLATE,
+        ] {
+            let codec = Codec::new(compression, None).unwrap();
+            let mut writer = BlockWriter::new(codec, 3, Duration::from_secs(1));
+            assert_eq!(writer.deadline(), None);
+            for m in &expected {
+                assert!(!writer.is_full());
+                writer.push(m).unwrap();
+            }
+            assert!(writer.is_full());
+            assert!(writer.deadline().is_some());
+
+            let block = writer.finish().unwrap().unwrap();
+            assert_eq!(writer.codec.messages(&block).unwrap(), expected);
+            assert!(writer.finish().unwrap().is_none());
+        }
+    }
+
+    #[test]
+    fn test_dictionary() {
+        use crate::encoder::Encoder;
+        use crate::message::{CourseOverGround, Heading, RateOfTurn, SpeedOverGround};
+        use crate::message::{NavigationStatus, Position, PositionReport};
+        use crate::sentence::{ChannelCode, TalkerID};
+
+        let mut encoder = Encoder::new(TalkerID::AI, ChannelCode::A);
+        // A handful of moored and slow-moving vessels reporting repeatedly
+        let corpus: Vec<String> = (0..2000u32)
+            .map(|i| {
+                let vessel = i % 10;
+                let m = PositionReport {
+                    msg_type: 1,
+                    repeat: 0,
+                    mmsi: Mmsi(227_006_760 + vessel),
+                    status: NavigationStatus::from((vessel % 2 * 5) as u8),
+                    rate_of_turn: RateOfTurn::NOT_AVAILABLE,
+                    speed: SpeedOverGround((vessel % 2) as u16),
+                    accuracy: false,
+                    position: Position {
+                        lon: 78828 + vessel as i32 * 1000 + (i / 100) as i32,
+                        lat: 29685346 + vessel as i32 * 1000,
+                    },
+                    course: CourseOverGround(367),
+                    heading: Heading::NOT_AVAILABLE,
+                    timestamp: (i / 10 % 60) as u8,
+                    maneuver: 0,
+                    raim: false,
+                    radio: 22136,
+                };
+                encoder.encode(&m).unwrap()[0].to_string()
+            })
+            .collect();
+
+        let data = train_dictionary(corpus.iter().map(String::as_str), 4096).unwrap();
+        let dictionary = Dictionary::new(&data);
+        assert_eq!(dictionary.hash(), Dictionary::new(&data).hash());
+        assert!(Codec::new(spec::Compression::DEFLATE, Some(Dictionary::new(&data))).is_err());
+
+        let plain = Codec::new(spec::Compression::ZSTD, None).unwrap();
+        let trained = Codec::new(spec::Compression::ZSTD, Some(dictionary)).unwrap();
+
+        // A single message, as sent in per-message frames
+        let message: spec::Message = corpus[1234].parse().unwrap();
+        let data = message.write_length_delimited_to_bytes().unwrap();
+        let compressed = trained.compress(&data).unwrap();
+        assert!(compressed.len() < plain.compress(&data).unwrap().len());
+        assert_eq!(trained.decompress(&compressed).unwrap(), data);
+    }
+}
@@ -0,0 +1,232 @@
+//! Seekable files. The compressor periodically writes a sync point, which
+//! resets the repeat window and starts a new frame (or block), and finishes
+//! the file with an index of the sync points. A fixed trailer after the
+//! index gives its offset:
+//!
+//! ```text
+//! Header | frames... | Index frame | "AISX" | index offset (u64 LE)
+//! ```
+//!
+//! The index is an ordinary message, which streaming decoders take as the
+//! end of the stream, so seekable files can still be piped through them.
+
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use crate::block::Codec;
+use crate::proto::spec;
+
+pub const TRAILER_MAGIC: &[u8; 4] = b"AISX";
+pub const TRAILER_LEN: u64 = 12;
+
+/// Counts the bytes written through it, to record frame offsets.
+#[derive(Debug)]
+pub struct CountingWriter<W> {
+    inner: W,
+    count: u64,
+}
+
+impl<W> CountingWriter<W> {
+    pub fn new(inner: W) -> Self {
+        CountingWriter { inner, count: 0 }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.count += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+pub fn write_trailer(writer: &mut impl Write, index_offset: u64) -> std::io::Result<()> {
+    writer.write_all(TRAILER_MAGIC)?;
+    writer.write_all(&index_offset.to_le_bytes())
+}
+
+/// Reads the index from the end of a seekable file, leaving the file
+/// position wherever the index ended. In block mode the index is the only
+/// message in the last block.
+pub fn read_index(file: &mut (impl Read + Seek), codec: &Codec) -> anyhow::Result<spec::Index> {
+    let len = file.seek(SeekFrom::End(0))?;
+    if len < TRAILER_LEN {
+        anyhow::bail!("file too short to be seekable");
+    }
+    file.seek(SeekFrom::End(-(TRAILER_LEN as i64)))?;
+    let mut trailer = [0; TRAILER_LEN as usize];
+    file.read_exact(&mut trailer)?;
+    if &trailer[..4] != TRAILER_MAGIC {
+        anyhow::bail!("no index found, was the file compressed with sync points?");
+    }
+    let offset = u64::from_le_bytes(trailer[4..].try_into().unwrap());
+    if offset >= len - TRAILER_LEN {
+        anyhow::bail!("index offset {offset} out of range");
+    }
+    file.seek(SeekFrom::Start(offset))?;
+    let mut input = protobuf::CodedInputStream::new(file);
+    let message = if codec.compression() == spec::Compression::UNCOMPRESSED {
+        input.read_message::<spec::Message>()?
+    } else {
+        let block = input.read_message::<spec::Block>()?;
+        codec
+            .messages(&block)?
+            .into_iter()
+            .next()
+            .unwrap_or_default()
+    };
+    if !message.has_index() {
+        anyhow::bail!("trailer doesn't point at an index");
+    }
+    Ok(message.index().clone())
+}
+
+impl spec::Index {
+    /// The last sync point at or before `time`, or the first one if `time`
+    /// is earlier than all of them.
+    pub fn find_time(&self, time: i64) -> Option<&spec::IndexEntry> {
+        let ix = self.entries.partition_point(|e| e.time() <= time);
+        self.entries.get(ix.saturating_sub(1))
+    }
+
+    /// The last sync point at or before message `n`.
+    pub fn find_message(&self, n: u64) -> Option<&spec::IndexEntry> {
+        let ix = self.entries.partition_point(|e| e.message_number() <= n);
+        self.entries.get(ix.saturating_sub(1))
+    }
+}
+
+/// Parses either seconds since the Unix epoch, such as "1700000000.5", or a
+/// UTC RFC 3339 timestamp such as "2023-11-14T22:13:20.5Z". Returns
+/// milliseconds since the epoch.
+pub fn parse_time(s: &str) -> anyhow::Result<i64> {
+    if let Ok(seconds) = s.parse::<f64>() {
+        return Ok((seconds * 1000.0).round() as i64);
+    }
+    let err = || anyhow::anyhow!("invalid time '{s}', expected e.g. 2023-11-14T22:13:20Z");
+    let s = s
+        .strip_suffix('Z')
+        .or_else(|| s.strip_suffix("+00:00"))
+        .ok_or_else(err)?;
+    let (date, time) = s.split_once(['T', ' ']).ok_or_else(err)?;
+
+    let mut date = date.splitn(3, '-').map(str::parse::<i64>);
+    let (Some(Ok(year)), Some(Ok(month)), Some(Ok(day))) = (date.next(), date.next(), date.next())
+    else {
+        return Err(err());
+    };
+    let (time, fraction) = time.split_once('.').unwrap_or((time, ""));
+    let mut time = time.splitn(3, ':').map(str::parse::<i64>);
+    let (Some(Ok(hour)), Some(Ok(minute)), Some(Ok(second))) =
+        (time.next(), time.next(), time.next())
+    else {
+        return Err(err());
+    };
+    if !(1..=12).contains(&month)
+        || !(1..=days_in_month(year, month)).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 60
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        return Err(err());
+    }
+    let millis = format!("{fraction:0<3}")[..3].parse::<i64>()?;
+
+    let days = days_from_civil(year, month, day);
+    Ok(((days * 24 + hour) * 60 + minute) * 60_000 + second * 1000 + millis)
+}
+
+pub(crate) fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 in the proleptic Gregorian calendar, from
+/// http://howardhinnant.github.io/date_algorithms.html
+pub(crate) fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use protobuf::Message;
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("1700000000").unwrap(), 1_700_000_000_000);
+        assert_eq!(parse_time("1700000000.25").unwrap(), 1_700_000_000_250);
+        assert_eq!(parse_time("1970-01-01T00:00:00Z").unwrap(), 0);
+        assert_eq!(
+            parse_time("2023-11-14T22:13:20Z").unwrap(),
+            1_700_000_000_000
+        );
+        assert_eq!(
+            parse_time("2023-11-14 22:13:20.5+00:00").unwrap(),
+            1_700_000_000_500
+        );
+        assert_eq!(parse_time("2000-03-01T00:00:00Z").unwrap(), 951_868_800_000);
+        assert!(parse_time("2023-11-14T22:13:20").is_err());
+        assert!(parse_time("2023-13-14T22:13:20Z").is_err());
+        assert!(parse_time("2026-02-31T00:00:00Z").is_err());
+        assert!(parse_time("2026-04-31T00:00:00Z").is_err());
+        assert!(parse_time("2026-02-29T00:00:00Z").is_err());
+        assert!(parse_time("2024-02-29T00:00:00Z").is_ok());
+        assert!(parse_time("1900-02-29T00:00:00Z").is_err());
+        assert!(parse_time("2000-02-29T00:00:00Z").is_ok());
+        assert!(parse_time("2026-12-31T00:00:00Z").is_ok());
+        assert!(parse_time("yesterday").is_err());
+    }
+
+    #[test]
+    fn test_index() {
+        let mut index = spec::Index::new();
+        for (offset, message_number, time) in [(10, 0, 1000), (50, 4, 2000), (90, 9, 3000)] {
+            let mut entry = spec::IndexEntry::new();
+            entry.set_offset(offset);
+            entry.set_message_number(message_number);
+            entry.set_time(time);
+            index.entries.push(entry);
+        }
+        assert_eq!(index.find_time(0).unwrap().offset(), 10);
+        assert_eq!(index.find_time(1999).unwrap().offset(), 10);
+        assert_eq!(index.find_time(2000).unwrap().offset(), 50);
+        assert_eq!(index.find_time(9999).unwrap().offset(), 90);
+        assert_eq!(index.find_message(3).unwrap().offset(), 10);
+        assert_eq!(index.find_message(9).unwrap().offset(), 90);
+        assert!(spec::Index::new().find_time(0).is_none());
+
+        // Some leading data, then the index frame and trailer
+        let mut file = CountingWriter::new(vec![1, 2, 3]);
+        file.count = 3;
+        let offset = file.count();
+        let mut message = spec::Message::new();
+        message.set_index(index.clone());
+        message.write_length_delimited_to_writer(&mut file).unwrap();
+        write_trailer(&mut file, offset).unwrap();
+
+        let codec = Codec::new(spec::Compression::UNCOMPRESSED, None).unwrap();
+        let mut cursor = std::io::Cursor::new(file.inner);
+        assert_eq!(read_index(&mut cursor, &codec).unwrap(), index);
+        let mut garbage = std::io::Cursor::new(vec![0; 20]);
+        assert!(read_index(&mut garbage, &codec).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+use bit_struct::u3;
+
+use crate::message::Payload;
+use crate::sentence::{ChannelCode, Metadata, Nmea, SentenceType, Talker};
+
+/// Maximum number of armored characters per sentence, keeping each
+/// `!AIVDM` sentence within the 82 character NMEA limit.
+pub const MAX_BODY_CHARS: usize = 60;
+
+/// Builds `!xxVDM` sentences from payloads, splitting them into multipart
+/// fragments where needed and assigning sequential message ids.
+#[derive(Debug)]
+pub struct Encoder {
+    pub talker: Talker,
+    /// VDM by default, VDO for own-ship reports
+    pub sentence_type: SentenceType,
+    pub channel: ChannelCode,
+    next_id: u8,
+}
+
+impl Encoder {
+    pub fn new(talker: impl Into<Talker>, channel: ChannelCode) -> Self {
+        Encoder {
+            talker: talker.into(),
+            sentence_type: SentenceType::VDM,
+            channel,
+            next_id: 0,
+        }
+    }
+
+    pub fn encode(&mut self, payload: &impl Payload) -> Result<Vec<Nmea<'static>>, &'static str> {
+        let (data, drop_bits) = payload.to_bits();
+        self.encode_bits(&data, drop_bits)
+    }
+
+    /// Armors an already bit-packed payload, as produced by
+    /// [`crate::bits::BitWriter::finish`].
+    pub fn encode_bits(
+        &mut self,
+        data: &[u8],
+        drop_bits: u3,
+    ) -> Result<Vec<Nmea<'static>>, &'static str> {
+        let (packed, fill_bits) = crate::armor::pack(data, drop_bits, 0)?;
+
+        // Each character carries 6 independent bits, so the armored string
+        // can be split anywhere. Only the last fragment has fill bits.
+        let chunks: Vec<&str> = packed
+            .as_bytes()
+            .chunks(MAX_BODY_CHARS)
+            .map(|c| std::str::from_utf8(c).expect("armored string is ascii"))
+            .collect();
+        let length = u8::try_from(chunks.len()).map_err(|_| "payload too long")?;
+        if length > 9 {
+            return Err("payload too long");
+        }
+
+        // Single sentences leave the message id empty
+        let message_id = if length == 1 {
+            0xff
+        } else {
+            let id = self.next_id;
+            self.next_id = (self.next_id + 1) % 10;
+            id
+        };
+
+        let sentences = chunks
+            .into_iter()
+            .enumerate()
+            .map(|(i, body)| {
+                let index = i as u8 + 1;
+                let mut sentence = Nmea {
+                    metadata: Metadata {
+                        talker: self.talker,
+                        sentence_type: self.sentence_type,
+                        length,
+                        index,
+                        message_id,
+                        channel: self.channel,
+                        fill_bits: if index == length {
+                            fill_bits
+                        } else {
+                            u3::new(0).unwrap()
+                        },
+                        checksum: 0,
+                    },
+                    body: body.to_owned().into(),
+                };
+                sentence.metadata.checksum = sentence.compute_checksum();
+                sentence
+            })
+            .collect();
+        Ok(sentences)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bits::BitWriter;
+    use crate::message::PositionReport;
+    use crate::sentence::TalkerID;
+
+    #[test]
+    fn test_single_sentence() {
+        let s = "!AIVDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*23";
+        let (data, drop_bits, _) = crate::armor::unpack("13HOI:0P0000VOHLCnHQKwvL05Ip", 0).unwrap();
+        let m = PositionReport::from_bits(&data, drop_bits).unwrap();
+
+        let mut encoder = Encoder::new(TalkerID::AI, ChannelCode::A);
+        let sentences = encoder.encode(&m).unwrap();
+        assert_eq!(sentences.len(), 1);
+        assert_eq!(sentences[0].to_string(), s);
+    }
+
+    #[test]
+    fn test_multipart() {
+        // Same length as a type 5 static and voyage report
+        let mut w = BitWriter::new();
+        w.write_u(6, 5);
+        for i in 0..418 {
+            w.write_bool(i % 3 == 0);
+        }
+        let (data, drop_bits) = w.finish();
+
+        let mut encoder = Encoder::new(TalkerID::AI, ChannelCode::B);
+        for expected_id in [0, 1] {
+            let sentences = encoder.encode_bits(&data, drop_bits).unwrap();
+            assert_eq!(sentences.len(), 2);
+            assert_eq!(sentences[0].body.len(), 60);
+            assert_eq!(sentences[1].body.len(), 11);
+
+            let mut body = String::new();
+            for (i, sentence) in sentences.iter().enumerate() {
+                let line = sentence.to_string();
+                assert!(crate::verify_checksum(&line).unwrap().0, "{line}");
+                let parsed = Nmea::parse(&line).unwrap();
+                assert_eq!(parsed.metadata.length, 2);
+                assert_eq!(parsed.metadata.index, i as u8 + 1);
+                assert_eq!(parsed.metadata.message_id, expected_id);
+                body.push_str(&parsed.body);
+            }
+            assert_eq!(sentences[0].metadata.fill_bits.value(), 0);
+            assert_eq!(sentences[1].metadata.fill_bits.value(), 2);
+
+            let (unpacked, unpacked_drop, _) = crate::armor::unpack(&body, 2).unwrap();
+            assert_eq!((unpacked, unpacked_drop), (data.clone(), drop_bits));
+        }
+    }
+}
@@ -1,7 +1,17 @@
+pub mod archive;
 pub mod armor;
+pub mod assembler;
+pub mod bits;
+pub mod block;
+pub mod container;
+pub mod encoder;
+pub mod message;
+pub mod mmsi;
 pub mod proto;
 pub mod sentence;
+pub mod window;
 
+/// Checks the checksum of a sentence, skipping any tag block in front of it.
 pub fn verify_checksum(s: &str) -> anyhow::Result<(bool, u8)> {
     use winnow::Parser;
     use winnow::error::StrContext;
@@ -9,7 +19,7 @@ pub fn verify_checksum(s: &str) -> anyhow::Result<(bool, u8)> {
 
     // helper function to parse out key segments
     fn parse_inner<'a>(s: &mut &'a str) -> winnow::Result<(&'a str, u8)> {
-        '!'.parse_next(s)?;
+        winnow::token::one_of(('!', '$')).parse_next(s)?;
         let main = take_until(1.., '*')
             .context(StrContext::Label("main"))
             .parse_next(s)?;
@@ -21,15 +31,48 @@ pub fn verify_checksum(s: &str) -> anyhow::Result<(bool, u8)> {
         Ok((main, checksum))
     }
 
+    let (_, s) = sentence::split_tag_block(s);
     let (main, checksum) = {
-        let mut s = s;
         parse_inner
-            .parse(&mut s)
+            .parse(s)
             .map_err(|e| anyhow::format_err!("\n{e}"))?
     };
-    let mut acc = checksum;
-    for char in main.chars() {
+    Ok((xor(main)? == checksum, checksum))
+}
+
+/// Computes the NMEA checksum of a sentence: the XOR of every character
+/// between the leading '!' or '$' and the '*'. Any checksum already present
+/// is ignored, so this can be used to fix up modified sentences.
+pub fn compute_checksum(s: &str) -> anyhow::Result<u8> {
+    let s = s.strip_prefix(['!', '$']).unwrap_or(s);
+    let main = match s.rfind('*') {
+        Some(ix) => &s[..ix],
+        None => s,
+    };
+    xor(main)
+}
+
+fn xor(s: &str) -> anyhow::Result<u8> {
+    let mut acc = 0;
+    for char in s.chars() {
         acc ^= u8::try_from(char)?;
     }
-    Ok((acc == 0, checksum))
+    Ok(acc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_checksum() {
+        let s = "!AIVDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*23";
+        assert_eq!(compute_checksum(s).unwrap(), 0x23);
+        assert_eq!(compute_checksum(&s[..s.len() - 3]).unwrap(), 0x23);
+        assert_eq!(
+            compute_checksum("!AIVDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*FF").unwrap(),
+            0x23
+        );
+        assert_eq!(compute_checksum("$GPGLL,,,,,,V,N*64").unwrap(), 0x64);
+    }
 }
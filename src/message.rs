@@ -0,0 +1,492 @@
+use std::fmt;
+
+use bit_struct::u3;
+
+use crate::bits::{BitReader, BitWriter, Overrun};
+use crate::mmsi::Mmsi;
+
+mod application;
+mod aton;
+mod base_station;
+mod binary;
+mod class_b;
+mod common;
+mod dgnss;
+mod long_range;
+mod management;
+mod position;
+mod safety;
+mod sar;
+mod static_data;
+mod static_voyage;
+mod validate;
+
+pub use application::*;
+pub use aton::*;
+pub use base_station::*;
+pub use binary::*;
+pub use class_b::*;
+pub use common::*;
+pub use dgnss::*;
+pub use long_range::*;
+pub use management::*;
+pub use position::*;
+pub use safety::*;
+pub use sar::*;
+pub use static_data::*;
+pub use static_voyage::*;
+pub use validate::*;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The payload ended before all fields were read
+    Overrun(Overrun),
+    /// The message type isn't one this decoder handles
+    MessageType(u8),
+    /// A field which selects the layout holds a reserved value
+    Reserved { field: &'static str, value: u8 },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::Overrun(e) => write!(fmt, "{e}"),
+            DecodeError::MessageType(t) => write!(fmt, "unsupported message type {t}"),
+            DecodeError::Reserved { field, value } => {
+                write!(fmt, "{field} uses reserved value {value}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<Overrun> for DecodeError {
+    fn from(e: Overrun) -> Self {
+        DecodeError::Overrun(e)
+    }
+}
+
+/// A typed AIS payload which can be read from and written to the bit
+/// representation produced by [`crate::armor`].
+pub trait Payload: Sized {
+    /// Decodes the payload, starting from the 6-bit message type.
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError>;
+
+    /// Encodes the payload, including the message type. Spare bits are
+    /// written as zero.
+    fn encode(&self, w: &mut BitWriter);
+
+    fn from_bits(data: &[u8], drop_bits: u3) -> Result<Self, DecodeError> {
+        Self::decode(&mut BitReader::new(data, drop_bits))
+    }
+
+    fn to_bits(&self) -> (Vec<u8>, u3) {
+        let mut w = BitWriter::new();
+        self.encode(&mut w);
+        w.finish()
+    }
+}
+
+/// Any AIS message, dispatched on the message type. Every type defined by
+/// ITU-R M.1371 has a variant; anything else is kept as [`Message::Unknown`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Message {
+    /// Types 1, 2 and 3
+    PositionReport(PositionReport),
+    /// Types 4 and 11
+    BaseStationReport(BaseStationReport),
+    /// Type 5
+    StaticVoyageData(StaticVoyageData),
+    /// Type 6
+    BinaryAddressedMessage(BinaryAddressedMessage),
+    /// Types 7 and 13
+    Acknowledge(Acknowledge),
+    /// Type 8
+    BinaryBroadcastMessage(BinaryBroadcastMessage),
+    /// Type 9
+    SarAircraftReport(SarAircraftReport),
+    /// Type 10
+    UtcInquiry(UtcInquiry),
+    /// Type 12
+    SafetyAddressedMessage(SafetyAddressedMessage),
+    /// Type 14
+    SafetyBroadcastMessage(SafetyBroadcastMessage),
+    /// Type 15
+    Interrogation(Interrogation),
+    /// Type 16
+    AssignmentCommand(AssignmentCommand),
+    /// Type 17
+    DgnssBroadcast(DgnssBroadcast),
+    /// Type 18
+    ClassBPositionReport(ClassBPositionReport),
+    /// Type 19
+    ExtendedClassBReport(ExtendedClassBReport),
+    /// Type 20
+    DataLinkManagement(DataLinkManagement),
+    /// Type 21
+    AidToNavigationReport(AidToNavigationReport),
+    /// Type 22
+    ChannelManagement(ChannelManagement),
+    /// Type 23
+    GroupAssignment(GroupAssignment),
+    /// Type 24, either part
+    StaticDataReport(StaticDataReport),
+    /// Types 25 and 26
+    SlotBinaryMessage(SlotBinaryMessage),
+    /// Type 27
+    LongRangeReport(LongRangeReport),
+    /// A payload which couldn't be decoded: a type without a decoder (0 or
+    /// 28-63), or any other type when the payload is too short or uses a
+    /// reserved layout, such as type 24 part 2. `bits` is the whole payload,
+    /// including the type, with `drop_bits` unused bits at the end.
+    /// [`Message::from_bits_validated`] reports why a known type ended up here.
+    Unknown {
+        msg_type: u8,
+        bits: Vec<u8>,
+        #[cfg_attr(feature = "serde", serde(serialize_with = "crate::bits::serialize_u3"))]
+        drop_bits: u3,
+    },
+}
+
+impl Message {
+    /// The source MMSI, unless the type is unknown
+    pub fn mmsi(&self) -> Option<Mmsi> {
+        let mmsi = match self {
+            Message::PositionReport(m) => m.mmsi,
+            Message::BaseStationReport(m) => m.mmsi,
+            Message::StaticVoyageData(m) => m.mmsi,
+            Message::BinaryAddressedMessage(m) => m.mmsi,
+            Message::Acknowledge(m) => m.mmsi,
+            Message::BinaryBroadcastMessage(m) => m.mmsi,
+            Message::SarAircraftReport(m) => m.mmsi,
+            Message::UtcInquiry(m) => m.mmsi,
+            Message::SafetyAddressedMessage(m) => m.mmsi,
+            Message::SafetyBroadcastMessage(m) => m.mmsi,
+            Message::Interrogation(m) => m.mmsi,
+            Message::AssignmentCommand(m) => m.mmsi,
+            Message::DgnssBroadcast(m) => m.mmsi,
+            Message::ClassBPositionReport(m) => m.mmsi,
+            Message::ExtendedClassBReport(m) => m.mmsi,
+            Message::DataLinkManagement(m) => m.mmsi,
+            Message::AidToNavigationReport(m) => m.mmsi,
+            Message::ChannelManagement(m) => m.mmsi,
+            Message::GroupAssignment(m) => m.mmsi,
+            Message::StaticDataReport(m) => m.mmsi,
+            Message::SlotBinaryMessage(m) => m.mmsi,
+            Message::LongRangeReport(m) => m.mmsi,
+            Message::Unknown { .. } => return None,
+        };
+        Some(mmsi)
+    }
+
+    pub fn msg_type(&self) -> u8 {
+        match self {
+            Message::PositionReport(m) => m.msg_type,
+            Message::BaseStationReport(m) => m.msg_type,
+            Message::StaticVoyageData(_) => 5,
+            Message::BinaryAddressedMessage(_) => 6,
+            Message::Acknowledge(m) => m.msg_type,
+            Message::BinaryBroadcastMessage(_) => 8,
+            Message::SarAircraftReport(_) => 9,
+            Message::UtcInquiry(_) => 10,
+            Message::SafetyAddressedMessage(_) => 12,
+            Message::SafetyBroadcastMessage(_) => 14,
+            Message::Interrogation(_) => 15,
+            Message::AssignmentCommand(_) => 16,
+            Message::DgnssBroadcast(_) => 17,
+            Message::ClassBPositionReport(_) => 18,
+            Message::ExtendedClassBReport(_) => 19,
+            Message::DataLinkManagement(_) => 20,
+            Message::AidToNavigationReport(_) => 21,
+            Message::ChannelManagement(_) => 22,
+            Message::GroupAssignment(_) => 23,
+            Message::StaticDataReport(_) => 24,
+            Message::SlotBinaryMessage(m) => m.msg_type,
+            Message::LongRangeReport(_) => 27,
+            Message::Unknown { msg_type, .. } => *msg_type,
+        }
+    }
+}
+
+impl Message {
+    /// Decodes into the variant for the message type, returning the error
+    /// where [`Payload::decode`] would fall back to [`Message::Unknown`].
+    fn decode_typed(r: &mut BitReader) -> Result<Self, DecodeError> {
+        // Peek at the type without consuming it, so each variant can decode
+        // (and validate) it itself
+        let msg_type = r.clone().read_u(6)? as u8;
+        match msg_type {
+            1..=3 => PositionReport::decode(r).map(Message::PositionReport),
+            4 | 11 => BaseStationReport::decode(r).map(Message::BaseStationReport),
+            5 => StaticVoyageData::decode(r).map(Message::StaticVoyageData),
+            6 => BinaryAddressedMessage::decode(r).map(Message::BinaryAddressedMessage),
+            7 | 13 => Acknowledge::decode(r).map(Message::Acknowledge),
+            8 => BinaryBroadcastMessage::decode(r).map(Message::BinaryBroadcastMessage),
+            9 => SarAircraftReport::decode(r).map(Message::SarAircraftReport),
+            10 => UtcInquiry::decode(r).map(Message::UtcInquiry),
+            12 => SafetyAddressedMessage::decode(r).map(Message::SafetyAddressedMessage),
+            14 => SafetyBroadcastMessage::decode(r).map(Message::SafetyBroadcastMessage),
+            15 => Interrogation::decode(r).map(Message::Interrogation),
+            16 => AssignmentCommand::decode(r).map(Message::AssignmentCommand),
+            17 => DgnssBroadcast::decode(r).map(Message::DgnssBroadcast),
+            18 => ClassBPositionReport::decode(r).map(Message::ClassBPositionReport),
+            19 => ExtendedClassBReport::decode(r).map(Message::ExtendedClassBReport),
+            20 => DataLinkManagement::decode(r).map(Message::DataLinkManagement),
+            21 => AidToNavigationReport::decode(r).map(Message::AidToNavigationReport),
+            22 => ChannelManagement::decode(r).map(Message::ChannelManagement),
+            23 => GroupAssignment::decode(r).map(Message::GroupAssignment),
+            24 => StaticDataReport::decode(r).map(Message::StaticDataReport),
+            25 | 26 => SlotBinaryMessage::decode(r).map(Message::SlotBinaryMessage),
+            27 => LongRangeReport::decode(r).map(Message::LongRangeReport),
+            _ => Message::decode_unknown(r),
+        }
+    }
+
+    /// Keeps the rest of the payload, including the message type, as-is
+    fn decode_unknown(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let msg_type = r.clone().read_u(6)? as u8;
+        let (bits, drop_bits) = r.read_bits(r.remaining())?;
+        Ok(Message::Unknown {
+            msg_type,
+            bits,
+            drop_bits,
+        })
+    }
+}
+
+impl Payload for Message {
+    /// Payloads which don't decode as their type, because they're truncated
+    /// or use a reserved layout, become [`Message::Unknown`]. This only
+    /// fails if there isn't even a message type.
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let start = r.clone();
+        Message::decode_typed(r).or_else(|_| {
+            *r = start;
+            Message::decode_unknown(r)
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        match self {
+            Message::PositionReport(m) => m.encode(w),
+            Message::BaseStationReport(m) => m.encode(w),
+            Message::StaticVoyageData(m) => m.encode(w),
+            Message::BinaryAddressedMessage(m) => m.encode(w),
+            Message::Acknowledge(m) => m.encode(w),
+            Message::BinaryBroadcastMessage(m) => m.encode(w),
+            Message::SarAircraftReport(m) => m.encode(w),
+            Message::UtcInquiry(m) => m.encode(w),
+            Message::SafetyAddressedMessage(m) => m.encode(w),
+            Message::SafetyBroadcastMessage(m) => m.encode(w),
+            Message::Interrogation(m) => m.encode(w),
+            Message::AssignmentCommand(m) => m.encode(w),
+            Message::DgnssBroadcast(m) => m.encode(w),
+            Message::ClassBPositionReport(m) => m.encode(w),
+            Message::ExtendedClassBReport(m) => m.encode(w),
+            Message::DataLinkManagement(m) => m.encode(w),
+            Message::AidToNavigationReport(m) => m.encode(w),
+            Message::ChannelManagement(m) => m.encode(w),
+            Message::GroupAssignment(m) => m.encode(w),
+            Message::StaticDataReport(m) => m.encode(w),
+            Message::SlotBinaryMessage(m) => m.encode(w),
+            Message::LongRangeReport(m) => m.encode(w),
+            Message::Unknown {
+                bits, drop_bits, ..
+            } => w.write_bits(bits, bits.len() * 8 - usize::from(drop_bits.value())),
+        }
+    }
+}
+
+impl From<PositionReport> for Message {
+    fn from(m: PositionReport) -> Self {
+        Message::PositionReport(m)
+    }
+}
+
+impl From<BaseStationReport> for Message {
+    fn from(m: BaseStationReport) -> Self {
+        Message::BaseStationReport(m)
+    }
+}
+
+impl From<StaticVoyageData> for Message {
+    fn from(m: StaticVoyageData) -> Self {
+        Message::StaticVoyageData(m)
+    }
+}
+
+impl From<BinaryAddressedMessage> for Message {
+    fn from(m: BinaryAddressedMessage) -> Self {
+        Message::BinaryAddressedMessage(m)
+    }
+}
+
+impl From<Acknowledge> for Message {
+    fn from(m: Acknowledge) -> Self {
+        Message::Acknowledge(m)
+    }
+}
+
+impl From<BinaryBroadcastMessage> for Message {
+    fn from(m: BinaryBroadcastMessage) -> Self {
+        Message::BinaryBroadcastMessage(m)
+    }
+}
+
+impl From<SarAircraftReport> for Message {
+    fn from(m: SarAircraftReport) -> Self {
+        Message::SarAircraftReport(m)
+    }
+}
+
+impl From<UtcInquiry> for Message {
+    fn from(m: UtcInquiry) -> Self {
+        Message::UtcInquiry(m)
+    }
+}
+
+impl From<SafetyAddressedMessage> for Message {
+    fn from(m: SafetyAddressedMessage) -> Self {
+        Message::SafetyAddressedMessage(m)
+    }
+}
+
+impl From<SafetyBroadcastMessage> for Message {
+    fn from(m: SafetyBroadcastMessage) -> Self {
+        Message::SafetyBroadcastMessage(m)
+    }
+}
+
+impl From<Interrogation> for Message {
+    fn from(m: Interrogation) -> Self {
+        Message::Interrogation(m)
+    }
+}
+
+impl From<AssignmentCommand> for Message {
+    fn from(m: AssignmentCommand) -> Self {
+        Message::AssignmentCommand(m)
+    }
+}
+
+impl From<DgnssBroadcast> for Message {
+    fn from(m: DgnssBroadcast) -> Self {
+        Message::DgnssBroadcast(m)
+    }
+}
+
+impl From<ClassBPositionReport> for Message {
+    fn from(m: ClassBPositionReport) -> Self {
+        Message::ClassBPositionReport(m)
+    }
+}
+
+impl From<ExtendedClassBReport> for Message {
+    fn from(m: ExtendedClassBReport) -> Self {
+        Message::ExtendedClassBReport(m)
+    }
+}
+
+impl From<DataLinkManagement> for Message {
+    fn from(m: DataLinkManagement) -> Self {
+        Message::DataLinkManagement(m)
+    }
+}
+
+impl From<AidToNavigationReport> for Message {
+    fn from(m: AidToNavigationReport) -> Self {
+        Message::AidToNavigationReport(m)
+    }
+}
+
+impl From<ChannelManagement> for Message {
+    fn from(m: ChannelManagement) -> Self {
+        Message::ChannelManagement(m)
+    }
+}
+
+impl From<GroupAssignment> for Message {
+    fn from(m: GroupAssignment) -> Self {
+        Message::GroupAssignment(m)
+    }
+}
+
+impl From<StaticDataReport> for Message {
+    fn from(m: StaticDataReport) -> Self {
+        Message::StaticDataReport(m)
+    }
+}
+
+impl From<SlotBinaryMessage> for Message {
+    fn from(m: SlotBinaryMessage) -> Self {
+        Message::SlotBinaryMessage(m)
+    }
+}
+
+impl From<LongRangeReport> for Message {
+    fn from(m: LongRangeReport) -> Self {
+        Message::LongRangeReport(m)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown() {
+        let mut w = BitWriter::new();
+        w.write_u(6, 28);
+        w.write_u(13, 0x1abc);
+        let (data, drop_bits) = w.finish();
+        let m = Message::from_bits(&data, drop_bits).unwrap();
+        assert_eq!(m.msg_type(), 28);
+        assert!(matches!(m, Message::Unknown { msg_type: 28, .. }));
+        assert_eq!(m.to_bits(), (data, drop_bits));
+
+        // Every type maps onto a variant, given enough bits
+        for msg_type in 0..64 {
+            let mut w = BitWriter::new();
+            w.write_u(6, msg_type);
+            for _ in 0..8 {
+                w.write_u(64, 0);
+            }
+            let (data, drop_bits) = w.finish();
+            let m = Message::from_bits(&data, drop_bits).unwrap();
+            assert_eq!(u64::from(m.msg_type()), msg_type);
+            assert_eq!(
+                matches!(m, Message::Unknown { .. }),
+                msg_type == 0 || msg_type > 27,
+                "{msg_type}"
+            );
+        }
+
+        // Even when they're too short for their type
+        let (data, drop_bits, _) = crate::armor::unpack("13HOI:0P0000", 0).unwrap();
+        let m = Message::from_bits(&data, drop_bits).unwrap();
+        assert!(matches!(m, Message::Unknown { msg_type: 1, .. }));
+        assert_eq!(m.to_bits(), (data, drop_bits));
+
+        // Or use a reserved layout, here type 24 part 2
+        let mut w = BitWriter::new();
+        w.write_u(6, 24);
+        w.write_u(32, 271041815);
+        w.write_u(2, 2);
+        w.write_u(64, 0);
+        w.write_u(64, 0);
+        let (data, drop_bits) = w.finish();
+        assert_eq!(
+            StaticDataReport::from_bits(&data, drop_bits),
+            Err(DecodeError::Reserved {
+                field: "part_number",
+                value: 2
+            })
+        );
+        let m = Message::from_bits(&data, drop_bits).unwrap();
+        assert!(matches!(m, Message::Unknown { msg_type: 24, .. }));
+
+        assert!(Message::from_bits(&[], drop_bits).is_err());
+    }
+}
@@ -0,0 +1,758 @@
+//! Application-specific payloads of binary messages (types 6 and 8), with
+//! the international (DAC 1) layouts of IMO SN.1/Circ.289.
+
+use std::collections::HashMap;
+
+use super::{BinaryData, DecodeError, MonthDayTime, Position};
+use crate::bits::{BitReader, BitWriter};
+
+/// Decodes the application data that follows the DAC and FI.
+pub type ApplicationDecoder = fn(&mut BitReader) -> Result<Application, DecodeError>;
+
+/// A decoded binary payload. Anything without a registered decoder is left
+/// as raw bits.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Application {
+    /// DAC 1, FI 31
+    MeteoHydro(MeteoHydro),
+    /// DAC 1, FI 22 (broadcast) or 23 (addressed)
+    AreaNotice(AreaNotice),
+    /// DAC 1, FI 27 (broadcast) or 28 (addressed)
+    RouteInfo(RouteInfo),
+    Raw(BinaryData),
+}
+
+/// Maps DAC and FI onto decoders. The default registry knows the IMO 289
+/// messages; regional layouts can be added with
+/// [`ApplicationRegistry::register`].
+#[derive(Debug, Clone)]
+pub struct ApplicationRegistry {
+    decoders: HashMap<(u16, u8), ApplicationDecoder>,
+}
+
+impl ApplicationRegistry {
+    pub fn empty() -> Self {
+        ApplicationRegistry {
+            decoders: HashMap::new(),
+        }
+    }
+
+    /// Adds or replaces the decoder for `dac` and `fi`.
+    pub fn register(&mut self, dac: u16, fi: u8, decoder: ApplicationDecoder) {
+        self.decoders.insert((dac, fi), decoder);
+    }
+
+    pub fn decode(&self, data: &BinaryData) -> Result<Application, DecodeError> {
+        match self.decoders.get(&(data.dac, data.fi)) {
+            Some(decoder) => decoder(&mut data.reader()),
+            None => Ok(Application::Raw(data.clone())),
+        }
+    }
+}
+
+impl Default for ApplicationRegistry {
+    fn default() -> Self {
+        let mut registry = Self::empty();
+        let area_notice: ApplicationDecoder =
+            |r| AreaNotice::decode(r).map(Application::AreaNotice);
+        let route_info: ApplicationDecoder = |r| RouteInfo::decode(r).map(Application::RouteInfo);
+        registry.register(1, 22, area_notice);
+        registry.register(1, 23, area_notice);
+        registry.register(1, 27, route_info);
+        registry.register(1, 28, route_info);
+        registry.register(1, 31, |r| {
+            MeteoHydro::decode(r).map(Application::MeteoHydro)
+        });
+        registry
+    }
+}
+
+/// Sea current at one depth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Current {
+    /// 1/10 knot, 251 if not available
+    pub speed: u8,
+    /// Degrees, 360 if not available
+    pub direction: u16,
+    /// Metres, always 0 for the surface current which has no depth field
+    pub depth: u8,
+}
+
+/// Wave or swell conditions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Waves {
+    /// 1/10 metre, 251 if not available
+    pub height: u8,
+    /// Seconds, 61 if not available
+    pub period: u8,
+    /// Degrees, 360 if not available
+    pub direction: u16,
+}
+
+impl Waves {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        Ok(Waves {
+            height: r.read_u(8)? as u8,
+            period: r.read_u(6)? as u8,
+            direction: r.read_u(9)? as u16,
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(8, self.height.into());
+        w.write_u(6, self.period.into());
+        w.write_u(9, self.direction.into());
+    }
+}
+
+/// Meteorological and hydrographic data. Fields are in transmitted units;
+/// the accessors convert the ones with offsets.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MeteoHydro {
+    /// Sent at 1/1000 minute resolution
+    pub position: Position,
+    pub accuracy: bool,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    /// Knots, 127 if not available
+    pub wind_speed: u8,
+    /// Knots, 127 if not available
+    pub wind_gust: u8,
+    /// Degrees, 360 if not available
+    pub wind_direction: u16,
+    /// Degrees, 360 if not available
+    pub wind_gust_direction: u16,
+    /// 1/10 °C, -1024 if not available
+    pub air_temperature: i16,
+    /// Percent, 101 if not available
+    pub humidity: u8,
+    /// 1/10 °C, 501 if not available
+    pub dew_point: i16,
+    /// hPa above 799, 511 if not available
+    pub pressure: u16,
+    /// 0 steady, 1 decreasing, 2 increasing, 3 not available
+    pub pressure_tendency: u8,
+    /// 1/10 nautical mile, with the top bit meaning "greater than". 127 if
+    /// not available.
+    pub visibility: u8,
+    /// Centimetres above -10 m, 4001 if not available
+    pub water_level: u16,
+    /// 0 steady, 1 decreasing, 2 increasing, 3 not available
+    pub water_level_trend: u8,
+    /// Surface current, then two at other depths
+    pub currents: [Current; 3],
+    pub waves: Waves,
+    pub swell: Waves,
+    /// Beaufort scale, 13 if not available
+    pub sea_state: u8,
+    /// 1/10 °C, 501 if not available
+    pub water_temperature: i16,
+    /// WMO precipitation type, 7 if not available
+    pub precipitation: u8,
+    /// 1/10 ‰, 510 if not available and 511 if there's no sensor
+    pub salinity: u16,
+    /// 0 no, 1 yes, 3 not available
+    pub ice: u8,
+}
+
+impl MeteoHydro {
+    pub fn air_temperature_celsius(&self) -> Option<f64> {
+        (self.air_temperature != -1024).then(|| f64::from(self.air_temperature) / 10.0)
+    }
+
+    /// 0 means 799 hPa or less, 402 means 1201 hPa or more
+    pub fn pressure_hpa(&self) -> Option<u16> {
+        (self.pressure <= 402).then_some(self.pressure + 799)
+    }
+
+    pub fn water_level_metres(&self) -> Option<f64> {
+        (self.water_level <= 4000).then(|| (f64::from(self.water_level) - 1000.0) / 100.0)
+    }
+
+    pub fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let position = Position::decode_coarse(r, 25, 24, 10)?;
+        let accuracy = r.read_bool()?;
+        let day = r.read_u(5)? as u8;
+        let hour = r.read_u(5)? as u8;
+        let minute = r.read_u(6)? as u8;
+        let wind_speed = r.read_u(7)? as u8;
+        let wind_gust = r.read_u(7)? as u8;
+        let wind_direction = r.read_u(9)? as u16;
+        let wind_gust_direction = r.read_u(9)? as u16;
+        let air_temperature = r.read_i(11)? as i16;
+        let humidity = r.read_u(7)? as u8;
+        let dew_point = r.read_i(10)? as i16;
+        let pressure = r.read_u(9)? as u16;
+        let pressure_tendency = r.read_u(2)? as u8;
+        let visibility = r.read_u(8)? as u8;
+        let water_level = r.read_u(12)? as u16;
+        let water_level_trend = r.read_u(2)? as u8;
+        let mut currents = [Current {
+            speed: 0,
+            direction: 0,
+            depth: 0,
+        }; 3];
+        for (i, current) in currents.iter_mut().enumerate() {
+            current.speed = r.read_u(8)? as u8;
+            current.direction = r.read_u(9)? as u16;
+            if i > 0 {
+                current.depth = r.read_u(5)? as u8;
+            }
+        }
+        let waves = Waves::decode(r)?;
+        let swell = Waves::decode(r)?;
+        let sea_state = r.read_u(4)? as u8;
+        let water_temperature = r.read_i(10)? as i16;
+        let precipitation = r.read_u(3)? as u8;
+        let salinity = r.read_u(9)? as u16;
+        let ice = r.read_u(2)? as u8;
+        Ok(MeteoHydro {
+            position,
+            accuracy,
+            day,
+            hour,
+            minute,
+            wind_speed,
+            wind_gust,
+            wind_direction,
+            wind_gust_direction,
+            air_temperature,
+            humidity,
+            dew_point,
+            pressure,
+            pressure_tendency,
+            visibility,
+            water_level,
+            water_level_trend,
+            currents,
+            waves,
+            swell,
+            sea_state,
+            water_temperature,
+            precipitation,
+            salinity,
+            ice,
+        })
+    }
+
+    /// Writes the application data, including the 10 spare bits at the end.
+    pub fn encode(&self, w: &mut BitWriter) {
+        self.position.encode_coarse(w, 25, 24, 10);
+        w.write_bool(self.accuracy);
+        w.write_u(5, self.day.into());
+        w.write_u(5, self.hour.into());
+        w.write_u(6, self.minute.into());
+        w.write_u(7, self.wind_speed.into());
+        w.write_u(7, self.wind_gust.into());
+        w.write_u(9, self.wind_direction.into());
+        w.write_u(9, self.wind_gust_direction.into());
+        w.write_i(11, self.air_temperature.into());
+        w.write_u(7, self.humidity.into());
+        w.write_i(10, self.dew_point.into());
+        w.write_u(9, self.pressure.into());
+        w.write_u(2, self.pressure_tendency.into());
+        w.write_u(8, self.visibility.into());
+        w.write_u(12, self.water_level.into());
+        w.write_u(2, self.water_level_trend.into());
+        for (i, current) in self.currents.iter().enumerate() {
+            w.write_u(8, current.speed.into());
+            w.write_u(9, current.direction.into());
+            if i > 0 {
+                w.write_u(5, current.depth.into());
+            }
+        }
+        self.waves.encode(w);
+        self.swell.encode(w);
+        w.write_u(4, self.sea_state.into());
+        w.write_i(10, self.water_temperature.into());
+        w.write_u(3, self.precipitation.into());
+        w.write_u(9, self.salinity.into());
+        w.write_u(2, self.ice.into());
+        w.write_u(10, 0);
+    }
+}
+
+/// A vertex of a polyline or polygon, relative to the previous one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PolyPoint {
+    /// 1/2 degree
+    pub angle: u16,
+    /// Scaled by the sub-area's scale factor
+    pub distance: u16,
+}
+
+/// One 87-bit sub-area of an area notice. Distances are multiplied by
+/// 10^`scale` metres; positions are sent at 1/1000 minute resolution.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum SubArea {
+    Circle {
+        scale: u8,
+        position: Position,
+        precision: u8,
+        radius: u16,
+    },
+    Rectangle {
+        scale: u8,
+        position: Position,
+        precision: u8,
+        east: u8,
+        north: u8,
+        /// Degrees
+        orientation: u16,
+    },
+    Sector {
+        scale: u8,
+        position: Position,
+        precision: u8,
+        radius: u16,
+        /// Degrees
+        left: u16,
+        right: u16,
+    },
+    Polyline {
+        scale: u8,
+        points: [PolyPoint; 4],
+    },
+    Polygon {
+        scale: u8,
+        points: [PolyPoint; 4],
+    },
+    /// Continues the text of the notice
+    Text(String),
+    /// Shapes 6 and 7
+    Reserved {
+        shape: u8,
+        data: Vec<u8>,
+    },
+}
+
+impl SubArea {
+    const BITS: usize = 87;
+
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let shape = r.read_u(3)? as u8;
+        let area = match shape {
+            0..=2 => {
+                let scale = r.read_u(2)? as u8;
+                let position = Position::decode_coarse(r, 25, 24, 10)?;
+                let precision = r.read_u(3)? as u8;
+                match shape {
+                    0 => {
+                        let radius = r.read_u(12)? as u16;
+                        r.skip(18)?;
+                        SubArea::Circle {
+                            scale,
+                            position,
+                            precision,
+                            radius,
+                        }
+                    }
+                    1 => {
+                        let east = r.read_u(8)? as u8;
+                        let north = r.read_u(8)? as u8;
+                        let orientation = r.read_u(9)? as u16;
+                        r.skip(5)?;
+                        SubArea::Rectangle {
+                            scale,
+                            position,
+                            precision,
+                            east,
+                            north,
+                            orientation,
+                        }
+                    }
+                    _ => SubArea::Sector {
+                        scale,
+                        position,
+                        precision,
+                        radius: r.read_u(12)? as u16,
+                        left: r.read_u(9)? as u16,
+                        right: r.read_u(9)? as u16,
+                    },
+                }
+            }
+            3 | 4 => {
+                let scale = r.read_u(2)? as u8;
+                let mut points = [PolyPoint::default(); 4];
+                for point in &mut points {
+                    point.angle = r.read_u(10)? as u16;
+                    point.distance = r.read_u(10)? as u16;
+                }
+                r.skip(2)?;
+                if shape == 3 {
+                    SubArea::Polyline { scale, points }
+                } else {
+                    SubArea::Polygon { scale, points }
+                }
+            }
+            5 => SubArea::Text(r.read_str(14)?),
+            _ => SubArea::Reserved {
+                shape,
+                data: r.read_bits(Self::BITS - 3)?.0,
+            },
+        };
+        Ok(area)
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        let header =
+            |w: &mut BitWriter, shape: u64, scale: &u8, position: &Position, precision: &u8| {
+                w.write_u(3, shape);
+                w.write_u(2, (*scale).into());
+                position.encode_coarse(w, 25, 24, 10);
+                w.write_u(3, (*precision).into());
+            };
+        let poly = |w: &mut BitWriter, shape: u64, scale: &u8, points: &[PolyPoint; 4]| {
+            w.write_u(3, shape);
+            w.write_u(2, (*scale).into());
+            for point in points {
+                w.write_u(10, point.angle.into());
+                w.write_u(10, point.distance.into());
+            }
+            w.write_u(2, 0);
+        };
+        match self {
+            SubArea::Circle {
+                scale,
+                position,
+                precision,
+                radius,
+            } => {
+                header(w, 0, scale, position, precision);
+                w.write_u(12, (*radius).into());
+                w.write_u(18, 0);
+            }
+            SubArea::Rectangle {
+                scale,
+                position,
+                precision,
+                east,
+                north,
+                orientation,
+            } => {
+                header(w, 1, scale, position, precision);
+                w.write_u(8, (*east).into());
+                w.write_u(8, (*north).into());
+                w.write_u(9, (*orientation).into());
+                w.write_u(5, 0);
+            }
+            SubArea::Sector {
+                scale,
+                position,
+                precision,
+                radius,
+                left,
+                right,
+            } => {
+                header(w, 2, scale, position, precision);
+                w.write_u(12, (*radius).into());
+                w.write_u(9, (*left).into());
+                w.write_u(9, (*right).into());
+            }
+            SubArea::Polyline { scale, points } => poly(w, 3, scale, points),
+            SubArea::Polygon { scale, points } => poly(w, 4, scale, points),
+            SubArea::Text(text) => {
+                w.write_u(3, 5);
+                w.write_str(14, text);
+            }
+            SubArea::Reserved { shape, data } => {
+                w.write_u(3, (*shape).into());
+                w.write_bits(data, Self::BITS - 3);
+            }
+        }
+    }
+}
+
+/// Area notice, with up to 10 sub-areas.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AreaNotice {
+    pub linkage_id: u16,
+    /// Notice description code, from the IMO 289 table
+    pub notice: u8,
+    pub start: MonthDayTime,
+    /// Minutes, 262143 if undefined
+    pub duration: u32,
+    pub areas: Vec<SubArea>,
+}
+
+impl AreaNotice {
+    pub fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let linkage_id = r.read_u(10)? as u16;
+        let notice = r.read_u(7)? as u8;
+        let start = MonthDayTime::decode(r)?;
+        let duration = r.read_u(18)? as u32;
+        let mut areas = Vec::new();
+        // Anything short of a whole sub-area is padding
+        while areas.len() < 10 && r.remaining() >= SubArea::BITS {
+            areas.push(SubArea::decode(r)?);
+        }
+        Ok(AreaNotice {
+            linkage_id,
+            notice,
+            start,
+            duration,
+            areas,
+        })
+    }
+
+    pub fn encode(&self, w: &mut BitWriter) {
+        w.write_u(10, self.linkage_id.into());
+        w.write_u(7, self.notice.into());
+        self.start.encode(w);
+        w.write_u(18, self.duration.into());
+        for area in self.areas.iter().take(10) {
+            area.encode(w);
+        }
+    }
+}
+
+/// Route information, with up to 16 waypoints.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RouteInfo {
+    pub linkage_id: u16,
+    /// Sender classification: 0 ship, 1 authority, 2-7 reserved
+    pub sender: u8,
+    /// Route type code, from the IMO 289 table
+    pub route_type: u8,
+    pub start: MonthDayTime,
+    /// Minutes, 262143 if undefined
+    pub duration: u32,
+    pub waypoints: Vec<Position>,
+}
+
+impl RouteInfo {
+    pub fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let linkage_id = r.read_u(10)? as u16;
+        let sender = r.read_u(3)? as u8;
+        let route_type = r.read_u(5)? as u8;
+        let start = MonthDayTime::decode(r)?;
+        let duration = r.read_u(18)? as u32;
+        let count = r.read_u(5)?.min(16);
+        let waypoints = (0..count)
+            .map(|_| Position::decode(r))
+            .collect::<Result<_, _>>()?;
+        Ok(RouteInfo {
+            linkage_id,
+            sender,
+            route_type,
+            start,
+            duration,
+            waypoints,
+        })
+    }
+
+    pub fn encode(&self, w: &mut BitWriter) {
+        w.write_u(10, self.linkage_id.into());
+        w.write_u(3, self.sender.into());
+        w.write_u(5, self.route_type.into());
+        self.start.encode(w);
+        w.write_u(18, self.duration.into());
+        let waypoints = &self.waypoints[..self.waypoints.len().min(16)];
+        w.write_u(5, waypoints.len() as u64);
+        for waypoint in waypoints {
+            waypoint.encode(w);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::{BinaryBroadcastMessage, Message, Payload};
+    use crate::mmsi::Mmsi;
+
+    fn binary(dac: u16, fi: u8, encode: impl FnOnce(&mut BitWriter)) -> BinaryData {
+        let mut w = BitWriter::new();
+        encode(&mut w);
+        let (data, drop_bits) = w.finish();
+        BinaryData {
+            dac,
+            fi,
+            data,
+            drop_bits,
+        }
+    }
+
+    #[test]
+    fn test_meteo_hydro() {
+        let not_available = Current {
+            speed: 251,
+            direction: 360,
+            depth: 0,
+        };
+        let m = MeteoHydro {
+            position: Position::from_degrees(4.1245, 51.9803),
+            accuracy: true,
+            day: 19,
+            hour: 10,
+            minute: 30,
+            wind_speed: 14,
+            wind_gust: 22,
+            wind_direction: 250,
+            wind_gust_direction: 255,
+            air_temperature: -35,
+            humidity: 87,
+            dew_point: -52,
+            pressure: 214,
+            pressure_tendency: 1,
+            visibility: 0x80 | 50,
+            water_level: 1123,
+            water_level_trend: 2,
+            currents: [
+                Current {
+                    speed: 12,
+                    direction: 90,
+                    depth: 0,
+                },
+                Current {
+                    depth: 5,
+                    ..not_available
+                },
+                not_available,
+            ],
+            waves: Waves {
+                height: 15,
+                period: 6,
+                direction: 240,
+            },
+            swell: Waves {
+                height: 251,
+                period: 61,
+                direction: 360,
+            },
+            sea_state: 4,
+            water_temperature: 112,
+            precipitation: 7,
+            salinity: 510,
+            ice: 0,
+        };
+        let data = binary(1, 31, |w| m.encode(w));
+        assert_eq!(data.bits(), 304);
+
+        // Whole message as broadcast
+        let message = BinaryBroadcastMessage {
+            repeat: 0,
+            mmsi: Mmsi(2442000),
+            data,
+        };
+        let (bits, drop_bits) = message.to_bits();
+        assert_eq!(bits.len() * 8 - usize::from(drop_bits.value()), 360);
+        let Message::BinaryBroadcastMessage(decoded) =
+            Message::from_bits(&bits, drop_bits).unwrap()
+        else {
+            panic!("expected a binary broadcast");
+        };
+        let registry = ApplicationRegistry::default();
+        let Application::MeteoHydro(decoded) = registry.decode(&decoded.data).unwrap() else {
+            panic!("expected meteo/hydro data");
+        };
+        assert_eq!(decoded, m);
+        assert_eq!(decoded.air_temperature_celsius(), Some(-3.5));
+        assert_eq!(decoded.pressure_hpa(), Some(1013));
+        assert_eq!(decoded.water_level_metres(), Some(1.23));
+        assert_eq!(decoded.position.longitude(), Some(4.1245));
+    }
+
+    #[test]
+    fn test_area_notice() {
+        let notice = AreaNotice {
+            linkage_id: 7,
+            notice: 24,
+            start: MonthDayTime {
+                month: 10,
+                day: 19,
+                hour: 6,
+                minute: 0,
+            },
+            duration: 720,
+            areas: vec![
+                SubArea::Circle {
+                    scale: 1,
+                    position: Position::from_degrees(-70.1, 41.5),
+                    precision: 4,
+                    radius: 120,
+                },
+                SubArea::Polygon {
+                    scale: 0,
+                    points: [
+                        PolyPoint {
+                            angle: 180,
+                            distance: 500,
+                        },
+                        PolyPoint {
+                            angle: 360,
+                            distance: 400,
+                        },
+                        PolyPoint::default(),
+                        PolyPoint::default(),
+                    ],
+                },
+                SubArea::Text("DIVING OPS".into()),
+            ],
+        };
+        let data = binary(1, 22, |w| {
+            notice.encode(w);
+            // Padding to a byte boundary isn't another sub-area
+            w.write_u(4, 0);
+        });
+        let Application::AreaNotice(decoded) =
+            ApplicationRegistry::default().decode(&data).unwrap()
+        else {
+            panic!("expected an area notice");
+        };
+        assert_eq!(decoded.areas.len(), 3);
+        assert_eq!(decoded.areas[2], SubArea::Text("DIVING OPS@@@@".into()));
+        assert_eq!(decoded.areas[..2], notice.areas[..2]);
+    }
+
+    #[test]
+    fn test_registry() {
+        let route = RouteInfo {
+            linkage_id: 1,
+            sender: 1,
+            route_type: 3,
+            start: MonthDayTime::NOT_AVAILABLE,
+            duration: 262143,
+            waypoints: vec![
+                Position::from_degrees(1.5, 51.0),
+                Position::from_degrees(1.6, 51.1),
+            ],
+        };
+        let data = binary(1, 28, |w| route.encode(w));
+        let mut registry = ApplicationRegistry::default();
+        assert_eq!(
+            registry.decode(&data).unwrap(),
+            Application::RouteInfo(route.clone())
+        );
+
+        // Unknown DAC/FI stay raw, until a decoder is registered
+        let regional = BinaryData { dac: 200, ..data };
+        assert_eq!(
+            registry.decode(&regional).unwrap(),
+            Application::Raw(regional.clone())
+        );
+        registry.register(200, 28, |r| {
+            RouteInfo::decode(r).map(Application::RouteInfo)
+        });
+        assert_eq!(
+            registry.decode(&regional).unwrap(),
+            Application::RouteInfo(route)
+        );
+        assert!(matches!(
+            ApplicationRegistry::empty().decode(&regional).unwrap(),
+            Application::Raw(_)
+        ));
+
+        let truncated = binary(1, 31, |w| w.write_u(8, 0));
+        assert!(registry.decode(&truncated).is_err());
+    }
+}
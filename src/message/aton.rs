@@ -0,0 +1,314 @@
+use super::{DecodeError, Dimensions, EpfdType, Payload, Position};
+use crate::bits::{BitReader, BitWriter};
+use crate::mmsi::Mmsi;
+
+/// Type of aid to navigation. Values 1-19 are fixed aids and 20-31
+/// floating ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum AidType {
+    /// Default, type not specified
+    Unspecified,
+    ReferencePoint,
+    Racon,
+    /// Fixed structure off shore, such as an oil platform
+    FixedStructure,
+    LightWithoutSectors,
+    LightWithSectors,
+    LeadingLightFront,
+    LeadingLightRear,
+    BeaconCardinalNorth,
+    BeaconCardinalEast,
+    BeaconCardinalSouth,
+    BeaconCardinalWest,
+    BeaconPortHand,
+    BeaconStarboardHand,
+    BeaconPreferredChannelPort,
+    BeaconPreferredChannelStarboard,
+    BeaconIsolatedDanger,
+    BeaconSafeWater,
+    BeaconSpecialMark,
+    CardinalNorth,
+    CardinalEast,
+    CardinalSouth,
+    CardinalWest,
+    PortHand,
+    StarboardHand,
+    PreferredChannelPort,
+    PreferredChannelStarboard,
+    IsolatedDanger,
+    SafeWater,
+    SpecialMark,
+    /// Light vessel, LANBY or rig
+    LightVessel,
+    /// 4 is reserved
+    Reserved(u8),
+}
+
+impl AidType {
+    pub fn is_floating(&self) -> bool {
+        u8::from(*self) >= 20
+    }
+}
+
+impl From<u8> for AidType {
+    fn from(x: u8) -> Self {
+        match x {
+            0 => AidType::Unspecified,
+            1 => AidType::ReferencePoint,
+            2 => AidType::Racon,
+            3 => AidType::FixedStructure,
+            5 => AidType::LightWithoutSectors,
+            6 => AidType::LightWithSectors,
+            7 => AidType::LeadingLightFront,
+            8 => AidType::LeadingLightRear,
+            9 => AidType::BeaconCardinalNorth,
+            10 => AidType::BeaconCardinalEast,
+            11 => AidType::BeaconCardinalSouth,
+            12 => AidType::BeaconCardinalWest,
+            13 => AidType::BeaconPortHand,
+            14 => AidType::BeaconStarboardHand,
+            15 => AidType::BeaconPreferredChannelPort,
+            16 => AidType::BeaconPreferredChannelStarboard,
+            17 => AidType::BeaconIsolatedDanger,
+            18 => AidType::BeaconSafeWater,
+            19 => AidType::BeaconSpecialMark,
+            20 => AidType::CardinalNorth,
+            21 => AidType::CardinalEast,
+            22 => AidType::CardinalSouth,
+            23 => AidType::CardinalWest,
+            24 => AidType::PortHand,
+            25 => AidType::StarboardHand,
+            26 => AidType::PreferredChannelPort,
+            27 => AidType::PreferredChannelStarboard,
+            28 => AidType::IsolatedDanger,
+            29 => AidType::SafeWater,
+            30 => AidType::SpecialMark,
+            31 => AidType::LightVessel,
+            x => AidType::Reserved(x),
+        }
+    }
+}
+
+impl From<AidType> for u8 {
+    fn from(t: AidType) -> Self {
+        match t {
+            AidType::Unspecified => 0,
+            AidType::ReferencePoint => 1,
+            AidType::Racon => 2,
+            AidType::FixedStructure => 3,
+            AidType::LightWithoutSectors => 5,
+            AidType::LightWithSectors => 6,
+            AidType::LeadingLightFront => 7,
+            AidType::LeadingLightRear => 8,
+            AidType::BeaconCardinalNorth => 9,
+            AidType::BeaconCardinalEast => 10,
+            AidType::BeaconCardinalSouth => 11,
+            AidType::BeaconCardinalWest => 12,
+            AidType::BeaconPortHand => 13,
+            AidType::BeaconStarboardHand => 14,
+            AidType::BeaconPreferredChannelPort => 15,
+            AidType::BeaconPreferredChannelStarboard => 16,
+            AidType::BeaconIsolatedDanger => 17,
+            AidType::BeaconSafeWater => 18,
+            AidType::BeaconSpecialMark => 19,
+            AidType::CardinalNorth => 20,
+            AidType::CardinalEast => 21,
+            AidType::CardinalSouth => 22,
+            AidType::CardinalWest => 23,
+            AidType::PortHand => 24,
+            AidType::StarboardHand => 25,
+            AidType::PreferredChannelPort => 26,
+            AidType::PreferredChannelStarboard => 27,
+            AidType::IsolatedDanger => 28,
+            AidType::SafeWater => 29,
+            AidType::SpecialMark => 30,
+            AidType::LightVessel => 31,
+            AidType::Reserved(x) => x,
+        }
+    }
+}
+
+/// Aid-to-navigation report, message type 21.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AidToNavigationReport {
+    pub repeat: u8,
+    pub mmsi: Mmsi,
+    pub aid_type: AidType,
+    /// As transmitted, padded with '@'. See [`AidToNavigationReport::full_name`].
+    pub name: String,
+    pub accuracy: bool,
+    pub position: Position,
+    pub dimensions: Dimensions,
+    pub epfd: EpfdType,
+    /// UTC second when the report was generated, 60-63 if unavailable
+    pub timestamp: u8,
+    /// Off its charted position. Only meaningful for floating aids when
+    /// `timestamp` is valid.
+    pub off_position: bool,
+    /// Reserved for regional applications
+    pub regional: u8,
+    pub raim: bool,
+    /// Virtual AtoN, which only exists as a broadcast
+    pub virtual_aid: bool,
+    /// Station is in assigned mode
+    pub assigned: bool,
+    /// Up to 14 more characters of the name, which make the message longer
+    /// than one slot and often split it across two sentences
+    pub name_extension: String,
+}
+
+impl AidToNavigationReport {
+    /// The name and its extension, without padding.
+    pub fn full_name(&self) -> String {
+        let name = super::trim_text(&self.name);
+        let extension = super::trim_text(&self.name_extension);
+        format!("{name}{extension}")
+    }
+}
+
+impl Payload for AidToNavigationReport {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let msg_type = r.read_u(6)? as u8;
+        if msg_type != 21 {
+            return Err(DecodeError::MessageType(msg_type));
+        }
+        let repeat = r.read_u(2)? as u8;
+        let mmsi = Mmsi(r.read_u(30)? as u32);
+        let aid_type = AidType::from(r.read_u(5)? as u8);
+        let name = r.read_str(20)?;
+        let accuracy = r.read_bool()?;
+        let position = Position::decode(r)?;
+        let dimensions = Dimensions::decode(r)?;
+        let epfd = EpfdType::from(r.read_u(4)? as u8);
+        let timestamp = r.read_u(6)? as u8;
+        let off_position = r.read_bool()?;
+        let regional = r.read_u(8)? as u8;
+        let raim = r.read_bool()?;
+        let virtual_aid = r.read_bool()?;
+        let assigned = r.read_bool()?;
+        r.skip(1)?;
+        // Whatever follows is the extension, padded to a byte boundary
+        let name_extension = r.read_str((r.remaining() / 6).min(14))?;
+        Ok(AidToNavigationReport {
+            repeat,
+            mmsi,
+            aid_type,
+            name,
+            accuracy,
+            position,
+            dimensions,
+            epfd,
+            timestamp,
+            off_position,
+            regional,
+            raim,
+            virtual_aid,
+            assigned,
+            name_extension,
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(6, 21);
+        w.write_u(2, self.repeat.into());
+        w.write_u(30, self.mmsi.into());
+        w.write_u(5, u8::from(self.aid_type).into());
+        w.write_str(20, &self.name);
+        w.write_bool(self.accuracy);
+        self.position.encode(w);
+        self.dimensions.encode(w);
+        w.write_u(4, u8::from(self.epfd).into());
+        w.write_u(6, self.timestamp.into());
+        w.write_bool(self.off_position);
+        w.write_u(8, self.regional.into());
+        w.write_bool(self.raim);
+        w.write_bool(self.virtual_aid);
+        w.write_bool(self.assigned);
+        w.write_u(1, 0);
+        w.write_str(
+            self.name_extension.chars().count().min(14),
+            &self.name_extension,
+        );
+        w.write_u((8 - w.len() % 8) % 8, 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::Message;
+    use crate::sentence::Nmea;
+
+    #[test]
+    fn test_decode_two_fragments() {
+        let lines = [
+            "!AIVDM,2,1,5,B,E1mg=5J1T4W0h97aRh6ba84<h2d;W:Te=eLvH50```q,0*46",
+            "!AIVDM,2,2,5,B,:D44QDlp0C1DU00,2*36",
+        ];
+        let mut body = String::new();
+        let mut fill_bits = 0;
+        for line in lines {
+            let nmea = Nmea::parse(line).unwrap();
+            body.push_str(&nmea.body);
+            fill_bits = nmea.metadata.fill_bits.value();
+        }
+        let (data, drop_bits, _) = crate::armor::unpack(&body, fill_bits).unwrap();
+        let m = AidToNavigationReport::from_bits(&data, drop_bits).unwrap();
+        assert_eq!(m.mmsi, Mmsi(123456789));
+        assert_eq!(m.aid_type, AidType::CardinalNorth);
+        assert!(m.aid_type.is_floating());
+        assert_eq!(m.name, "CHINA ROSE MURPHY EX");
+        assert_eq!(m.name_extension, "PRESS ALERT@");
+        assert_eq!(m.full_name(), "CHINA ROSE MURPHY EXPRESS ALERT");
+        assert_eq!(
+            m.dimensions,
+            Dimensions {
+                to_bow: 5,
+                to_stern: 5,
+                to_port: 5,
+                to_starboard: 5
+            }
+        );
+        assert_eq!(m.epfd, EpfdType::Gps);
+        assert_eq!(m.timestamp, 50);
+        assert!(!m.off_position);
+        assert!(!m.virtual_aid);
+
+        // The sender padded past the byte boundary; re-encoding doesn't
+        let (bits, drop_bits) = m.to_bits();
+        assert_eq!(bits.len() * 8 - usize::from(drop_bits.value()), 272 + 72);
+        assert_eq!(
+            Message::from_bits(&bits, drop_bits).unwrap(),
+            Message::AidToNavigationReport(m)
+        );
+    }
+
+    #[test]
+    fn test_without_extension() {
+        let m = AidToNavigationReport {
+            repeat: 0,
+            mmsi: Mmsi(992351001),
+            aid_type: AidType::Reserved(4),
+            name: "WRECK MARK".into(),
+            accuracy: true,
+            position: Position::from_degrees(-1.25, 50.75),
+            dimensions: Dimensions::default(),
+            epfd: EpfdType::Surveyed,
+            timestamp: 61,
+            off_position: false,
+            regional: 0,
+            raim: false,
+            virtual_aid: true,
+            assigned: false,
+            name_extension: String::new(),
+        };
+        let (data, drop_bits) = m.to_bits();
+        assert_eq!((data.len(), drop_bits.value()), (34, 0));
+        let decoded = AidToNavigationReport::from_bits(&data, drop_bits).unwrap();
+        assert_eq!(decoded.full_name(), "WRECK MARK");
+        assert_eq!(u8::from(decoded.aid_type), 4);
+        assert_eq!(decoded.to_bits(), (data, drop_bits));
+    }
+}
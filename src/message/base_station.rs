@@ -0,0 +1,216 @@
+use super::{DecodeError, EpfdType, Payload, Position, SotdmaState};
+use crate::bits::{BitReader, BitWriter};
+use crate::mmsi::Mmsi;
+
+/// UTC date and time as broadcast by base stations. Each field has its own
+/// "not available" value: year 0, month 0, day 0, hour 24, minute 60 and
+/// second 60.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct UtcDateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl UtcDateTime {
+    pub const NOT_AVAILABLE: UtcDateTime = UtcDateTime {
+        year: 0,
+        month: 0,
+        day: 0,
+        hour: 24,
+        minute: 60,
+        second: 60,
+    };
+
+    /// Milliseconds since the Unix epoch, if every field is available
+    pub fn unix_millis(&self) -> Option<i64> {
+        let (year, month, day) = (self.year.into(), self.month.into(), self.day.into());
+        if year == 0
+            || !(1..=12).contains(&month)
+            || !(1..=crate::container::days_in_month(year, month)).contains(&day)
+            || self.hour > 23
+            || self.minute > 59
+            || self.second > 59
+        {
+            return None;
+        }
+        let days = crate::container::days_from_civil(year, month, day);
+        let seconds = ((days * 24 + i64::from(self.hour)) * 60 + i64::from(self.minute)) * 60
+            + i64::from(self.second);
+        Some(seconds * 1000)
+    }
+}
+
+/// Base station report (type 4) or UTC/date response (type 11), which share
+/// a layout.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BaseStationReport {
+    pub msg_type: u8,
+    pub repeat: u8,
+    pub mmsi: Mmsi,
+    pub time: UtcDateTime,
+    pub accuracy: bool,
+    pub position: Position,
+    pub epfd: EpfdType,
+    pub raim: bool,
+    pub radio: SotdmaState,
+}
+
+impl BaseStationReport {
+    /// The station's clock minus `received`, in milliseconds. `received`
+    /// is milliseconds since the Unix epoch, such as a tag block timestamp
+    /// from [`crate::sentence::tag_block_time`] or the time the line was
+    /// read. The report only has whole seconds, so expect up to a second of
+    /// rounding.
+    pub fn clock_skew(&self, received: i64) -> Option<i64> {
+        Some(self.time.unix_millis()? - received)
+    }
+}
+
+impl Payload for BaseStationReport {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let msg_type = r.read_u(6)? as u8;
+        if msg_type != 4 && msg_type != 11 {
+            return Err(DecodeError::MessageType(msg_type));
+        }
+        let repeat = r.read_u(2)? as u8;
+        let mmsi = Mmsi(r.read_u(30)? as u32);
+        let time = UtcDateTime {
+            year: r.read_u(14)? as u16,
+            month: r.read_u(4)? as u8,
+            day: r.read_u(5)? as u8,
+            hour: r.read_u(5)? as u8,
+            minute: r.read_u(6)? as u8,
+            second: r.read_u(6)? as u8,
+        };
+        let accuracy = r.read_bool()?;
+        let position = Position::decode(r)?;
+        let epfd = EpfdType::from(r.read_u(4)? as u8);
+        r.skip(10)?;
+        let raim = r.read_bool()?;
+        let radio = SotdmaState::from_raw(r.read_u(19)? as u32);
+        Ok(BaseStationReport {
+            msg_type,
+            repeat,
+            mmsi,
+            time,
+            accuracy,
+            position,
+            epfd,
+            raim,
+            radio,
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(6, self.msg_type.into());
+        w.write_u(2, self.repeat.into());
+        w.write_u(30, self.mmsi.into());
+        w.write_u(14, self.time.year.into());
+        w.write_u(4, self.time.month.into());
+        w.write_u(5, self.time.day.into());
+        w.write_u(5, self.time.hour.into());
+        w.write_u(6, self.time.minute.into());
+        w.write_u(6, self.time.second.into());
+        w.write_bool(self.accuracy);
+        self.position.encode(w);
+        w.write_u(4, u8::from(self.epfd).into());
+        w.write_u(10, 0);
+        w.write_bool(self.raim);
+        w.write_u(19, self.radio.raw().into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::{Message, SotdmaSubMessage};
+
+    #[test]
+    fn test_decode_type_4() {
+        let (data, drop_bits, _) = crate::armor::unpack("403OviQuMGCqWrRO9>E6fE700@GO", 0).unwrap();
+        let m = BaseStationReport::from_bits(&data, drop_bits).unwrap();
+        assert_eq!(m.msg_type, 4);
+        assert_eq!(m.mmsi, Mmsi(3669702));
+        assert_eq!(
+            m.time,
+            UtcDateTime {
+                year: 2007,
+                month: 5,
+                day: 14,
+                hour: 19,
+                minute: 57,
+                second: 39
+            }
+        );
+        // 2007-05-14T19:57:39Z
+        assert_eq!(m.time.unix_millis(), Some(1_179_172_659_000));
+        assert!(m.accuracy);
+        assert_eq!(m.position.longitude(), Some(-76.35236166666667));
+        assert_eq!(m.position.latitude(), Some(36.883766666666666));
+        assert_eq!(m.epfd, EpfdType::Surveyed);
+        assert!(!m.raim);
+        assert_eq!(m.radio.raw(), 67039);
+        assert_eq!(m.radio.sub_message(), SotdmaSubMessage::SlotNumber(1503));
+
+        assert_eq!(m.clock_skew(1_179_172_658_500), Some(500));
+        for (month, day) in [(2, 31), (2, 29), (4, 31)] {
+            let time = UtcDateTime {
+                year: 2023,
+                month,
+                day,
+                ..m.time
+            };
+            assert_eq!(time.unix_millis(), None, "{month}-{day}");
+        }
+        let leap_day = UtcDateTime {
+            year: 2024,
+            month: 2,
+            day: 29,
+            ..m.time
+        };
+        assert!(leap_day.unix_millis().is_some());
+        assert_eq!(m.to_bits(), (data.clone(), drop_bits));
+        assert!(matches!(
+            Message::from_bits(&data, drop_bits).unwrap(),
+            Message::BaseStationReport(_)
+        ));
+    }
+
+    #[test]
+    fn test_type_11_round_trip() {
+        let m = BaseStationReport {
+            msg_type: 11,
+            repeat: 0,
+            mmsi: Mmsi(227006760),
+            time: UtcDateTime::NOT_AVAILABLE,
+            accuracy: false,
+            position: Position::NOT_AVAILABLE,
+            epfd: EpfdType::Gps,
+            raim: true,
+            radio: SotdmaState {
+                sync_state: 1,
+                slot_timeout: 1,
+                sub_message: (13 << 9) | (45 << 2),
+            },
+        };
+        let (data, drop_bits) = m.to_bits();
+        assert_eq!(data.len() * 8 - usize::from(drop_bits.value()), 168);
+        let decoded = BaseStationReport::from_bits(&data, drop_bits).unwrap();
+        assert_eq!(decoded, m);
+        assert_eq!(decoded.time.unix_millis(), None);
+        assert_eq!(decoded.clock_skew(0), None);
+        assert_eq!(
+            decoded.radio.sub_message(),
+            SotdmaSubMessage::UtcHourMinute {
+                hour: 13,
+                minute: 45
+            }
+        );
+    }
+}
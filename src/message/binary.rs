@@ -0,0 +1,386 @@
+use bit_struct::u3;
+
+use super::{DecodeError, Payload};
+use crate::bits::{BitReader, BitWriter};
+use crate::mmsi::Mmsi;
+
+/// Application payload of a binary message, identified by its designated
+/// area code (DAC) and function identifier (FI).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BinaryData {
+    pub dac: u16,
+    pub fi: u8,
+    /// Left-aligned, with `drop_bits` unused bits at the end, as read by
+    /// [`BitReader::read_bits`]
+    pub data: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::bits::serialize_u3"))]
+    pub drop_bits: u3,
+}
+
+impl BinaryData {
+    /// Length of the application data in bits
+    pub fn bits(&self) -> usize {
+        self.data.len() * 8 - usize::from(self.drop_bits.value())
+    }
+
+    /// A reader over the application data, for application decoders.
+    pub fn reader(&self) -> BitReader<'_> {
+        BitReader::new(&self.data, self.drop_bits)
+    }
+
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let dac = r.read_u(10)? as u16;
+        let fi = r.read_u(6)? as u8;
+        let (data, drop_bits) = r.read_bits(r.remaining())?;
+        Ok(BinaryData {
+            dac,
+            fi,
+            data,
+            drop_bits,
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(10, self.dac.into());
+        w.write_u(6, self.fi.into());
+        w.write_bits(&self.data, self.bits());
+    }
+}
+
+/// Addressed binary message, type 6.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BinaryAddressedMessage {
+    pub repeat: u8,
+    pub mmsi: Mmsi,
+    pub sequence: u8,
+    pub dest_mmsi: Mmsi,
+    pub retransmit: bool,
+    pub data: BinaryData,
+}
+
+impl Payload for BinaryAddressedMessage {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let msg_type = r.read_u(6)? as u8;
+        if msg_type != 6 {
+            return Err(DecodeError::MessageType(msg_type));
+        }
+        let repeat = r.read_u(2)? as u8;
+        let mmsi = Mmsi(r.read_u(30)? as u32);
+        let sequence = r.read_u(2)? as u8;
+        let dest_mmsi = Mmsi(r.read_u(30)? as u32);
+        let retransmit = r.read_bool()?;
+        r.skip(1)?;
+        let data = BinaryData::decode(r)?;
+        Ok(BinaryAddressedMessage {
+            repeat,
+            mmsi,
+            sequence,
+            dest_mmsi,
+            retransmit,
+            data,
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(6, 6);
+        w.write_u(2, self.repeat.into());
+        w.write_u(30, self.mmsi.into());
+        w.write_u(2, self.sequence.into());
+        w.write_u(30, self.dest_mmsi.into());
+        w.write_bool(self.retransmit);
+        w.write_u(1, 0);
+        self.data.encode(w);
+    }
+}
+
+/// Broadcast binary message, type 8.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BinaryBroadcastMessage {
+    pub repeat: u8,
+    pub mmsi: Mmsi,
+    pub data: BinaryData,
+}
+
+impl Payload for BinaryBroadcastMessage {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let msg_type = r.read_u(6)? as u8;
+        if msg_type != 8 {
+            return Err(DecodeError::MessageType(msg_type));
+        }
+        let repeat = r.read_u(2)? as u8;
+        let mmsi = Mmsi(r.read_u(30)? as u32);
+        r.skip(2)?;
+        let data = BinaryData::decode(r)?;
+        Ok(BinaryBroadcastMessage { repeat, mmsi, data })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(6, 8);
+        w.write_u(2, self.repeat.into());
+        w.write_u(30, self.mmsi.into());
+        w.write_u(2, 0);
+        self.data.encode(w);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Acknowledgement {
+    pub mmsi: Mmsi,
+    pub sequence: u8,
+}
+
+/// Binary acknowledge (type 7) or safety-related acknowledge (type 13), for
+/// up to four addressed messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Acknowledge {
+    pub msg_type: u8,
+    pub repeat: u8,
+    pub mmsi: Mmsi,
+    pub acks: Vec<Acknowledgement>,
+}
+
+impl Payload for Acknowledge {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let msg_type = r.read_u(6)? as u8;
+        if msg_type != 7 && msg_type != 13 {
+            return Err(DecodeError::MessageType(msg_type));
+        }
+        let repeat = r.read_u(2)? as u8;
+        let mmsi = Mmsi(r.read_u(30)? as u32);
+        r.skip(2)?;
+        let mut acks = Vec::new();
+        while acks.len() < 4 && r.remaining() >= 32 {
+            acks.push(Acknowledgement {
+                mmsi: Mmsi(r.read_u(30)? as u32),
+                sequence: r.read_u(2)? as u8,
+            });
+        }
+        Ok(Acknowledge {
+            msg_type,
+            repeat,
+            mmsi,
+            acks,
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(6, self.msg_type.into());
+        w.write_u(2, self.repeat.into());
+        w.write_u(30, self.mmsi.into());
+        w.write_u(2, 0);
+        for ack in self.acks.iter().take(4) {
+            w.write_u(30, ack.mmsi.into());
+            w.write_u(2, ack.sequence.into());
+        }
+    }
+}
+
+/// Single slot (type 25) or multiple slot (type 26) binary message, which
+/// may be addressed and may carry a DAC and FI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SlotBinaryMessage {
+    pub msg_type: u8,
+    pub repeat: u8,
+    pub mmsi: Mmsi,
+    pub dest_mmsi: Option<Mmsi>,
+    /// DAC and FI, if the data is structured
+    pub application: Option<(u16, u8)>,
+    /// Left-aligned, with `drop_bits` unused bits at the end
+    pub data: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::bits::serialize_u3"))]
+    pub drop_bits: u3,
+    /// Communication state of type 26, with the selector flag in the top
+    /// bit. Always 0 for type 25.
+    pub radio: u32,
+}
+
+impl SlotBinaryMessage {
+    /// The payload as [`BinaryData`] for an
+    /// [`super::ApplicationRegistry`], if it's structured.
+    pub fn binary_data(&self) -> Option<BinaryData> {
+        let (dac, fi) = self.application?;
+        Some(BinaryData {
+            dac,
+            fi,
+            data: self.data.clone(),
+            drop_bits: self.drop_bits,
+        })
+    }
+}
+
+impl Payload for SlotBinaryMessage {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let msg_type = r.read_u(6)? as u8;
+        if msg_type != 25 && msg_type != 26 {
+            return Err(DecodeError::MessageType(msg_type));
+        }
+        let repeat = r.read_u(2)? as u8;
+        let mmsi = Mmsi(r.read_u(30)? as u32);
+        let addressed = r.read_bool()?;
+        let structured = r.read_bool()?;
+        let dest_mmsi = if addressed {
+            let dest = Mmsi(r.read_u(30)? as u32);
+            r.skip(2)?;
+            Some(dest)
+        } else {
+            None
+        };
+        let application = if structured {
+            Some((r.read_u(10)? as u16, r.read_u(6)? as u8))
+        } else {
+            None
+        };
+        let radio_bits = if msg_type == 26 { 20 } else { 0 };
+        let (data, drop_bits) = r.read_bits(r.remaining().saturating_sub(radio_bits))?;
+        let radio = r.read_u(radio_bits)? as u32;
+        Ok(SlotBinaryMessage {
+            msg_type,
+            repeat,
+            mmsi,
+            dest_mmsi,
+            application,
+            data,
+            drop_bits,
+            radio,
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(6, self.msg_type.into());
+        w.write_u(2, self.repeat.into());
+        w.write_u(30, self.mmsi.into());
+        w.write_bool(self.dest_mmsi.is_some());
+        w.write_bool(self.application.is_some());
+        if let Some(dest) = self.dest_mmsi {
+            w.write_u(30, dest.into());
+            w.write_u(2, 0);
+        }
+        if let Some((dac, fi)) = self.application {
+            w.write_u(10, dac.into());
+            w.write_u(6, fi.into());
+        }
+        let bits = self.data.len() * 8 - usize::from(self.drop_bits.value());
+        w.write_bits(&self.data, bits);
+        if self.msg_type == 26 {
+            w.write_u(20, self.radio.into());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::Message;
+
+    #[test]
+    fn test_decode_type_8() {
+        let (data, drop_bits, _) =
+            crate::armor::unpack("85Mwp`1Kf3aCnsNvBWLi=wQuNhA5t43N`5nCuI=p<IBfVqnMgPGs", 0)
+                .unwrap();
+        let m = BinaryBroadcastMessage::from_bits(&data, drop_bits).unwrap();
+        assert_eq!(m.mmsi, Mmsi(366999712));
+        assert_eq!((m.data.dac, m.data.fi), (366, 56));
+        assert_eq!(m.data.bits(), 256);
+        assert_eq!(m.data.reader().read_u(8).unwrap(), 58);
+        assert_eq!(m.to_bits(), (data.clone(), drop_bits));
+        assert!(matches!(
+            Message::from_bits(&data, drop_bits).unwrap(),
+            Message::BinaryBroadcastMessage(_)
+        ));
+    }
+
+    #[test]
+    fn test_type_6_round_trip() {
+        let m = BinaryAddressedMessage {
+            repeat: 1,
+            mmsi: Mmsi(235009802),
+            sequence: 3,
+            dest_mmsi: Mmsi(992351234),
+            retransmit: true,
+            data: BinaryData {
+                dac: 235,
+                fi: 10,
+                data: vec![0xab, 0xcd, 0xe0],
+                drop_bits: u3::new(3).unwrap(),
+            },
+        };
+        let (data, drop_bits) = m.to_bits();
+        assert_eq!(data.len() * 8 - usize::from(drop_bits.value()), 88 + 21);
+        assert_eq!(
+            BinaryAddressedMessage::from_bits(&data, drop_bits).unwrap(),
+            m
+        );
+        assert!(matches!(
+            Message::from_bits(&data, drop_bits).unwrap(),
+            Message::BinaryAddressedMessage(_)
+        ));
+    }
+
+    #[test]
+    fn test_decode_type_7() {
+        let (data, drop_bits, _) = crate::armor::unpack("702R5`hwCjq8", 0).unwrap();
+        let m = Acknowledge::from_bits(&data, drop_bits).unwrap();
+        assert_eq!(m.msg_type, 7);
+        assert_eq!(m.mmsi, Mmsi(2655651));
+        assert_eq!(
+            m.acks,
+            [Acknowledgement {
+                mmsi: Mmsi(265538450),
+                sequence: 0
+            }]
+        );
+        assert_eq!(m.to_bits(), (data.clone(), drop_bits));
+
+        let safety = Acknowledge { msg_type: 13, ..m };
+        let (data, drop_bits) = safety.to_bits();
+        assert_eq!(
+            Message::from_bits(&data, drop_bits).unwrap(),
+            Message::Acknowledge(safety)
+        );
+    }
+
+    #[test]
+    fn test_slot_binary_round_trip() {
+        let m = SlotBinaryMessage {
+            msg_type: 25,
+            repeat: 0,
+            mmsi: Mmsi(440006460),
+            dest_mmsi: Some(Mmsi(134218384)),
+            application: Some((1, 0)),
+            data: vec![0x12, 0x34, 0x40],
+            drop_bits: u3::new(6).unwrap(),
+            radio: 0,
+        };
+        let (data, drop_bits) = m.to_bits();
+        assert_eq!(data.len() * 8 - usize::from(drop_bits.value()), 88 + 18);
+        assert_eq!(
+            Message::from_bits(&data, drop_bits).unwrap(),
+            Message::SlotBinaryMessage(m.clone())
+        );
+        assert_eq!(m.binary_data().unwrap().bits(), 18);
+
+        let multi = SlotBinaryMessage {
+            msg_type: 26,
+            dest_mmsi: None,
+            application: None,
+            radio: 0x80123,
+            ..m
+        };
+        assert_eq!(multi.binary_data(), None);
+        let (data, drop_bits) = multi.to_bits();
+        assert_eq!(
+            data.len() * 8 - usize::from(drop_bits.value()),
+            40 + 18 + 20
+        );
+        assert_eq!(
+            SlotBinaryMessage::from_bits(&data, drop_bits).unwrap(),
+            multi
+        );
+    }
+}
@@ -0,0 +1,293 @@
+use super::{
+    CourseOverGround, DecodeError, Dimensions, EpfdType, Heading, Payload, Position, SotdmaState,
+    SpeedOverGround,
+};
+use crate::bits::{BitReader, BitWriter};
+use crate::mmsi::Mmsi;
+
+/// Standard Class B position report, message type 18.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ClassBPositionReport {
+    pub repeat: u8,
+    pub mmsi: Mmsi,
+    pub speed: SpeedOverGround,
+    pub accuracy: bool,
+    pub position: Position,
+    pub course: CourseOverGround,
+    pub heading: Heading,
+    /// UTC second when the report was generated, 60-63 if unavailable
+    pub timestamp: u8,
+    /// Reserved for regional applications
+    pub regional: u8,
+    /// Carrier-sense (CS) unit rather than SOTDMA
+    pub cs_unit: bool,
+    /// Has a display for messages 12 and 14
+    pub display: bool,
+    /// Has a DSC receiver
+    pub dsc: bool,
+    /// Can use the whole marine band
+    pub band: bool,
+    /// Accepts channel management by message 22
+    pub msg22: bool,
+    /// Station is in assigned mode
+    pub assigned: bool,
+    pub raim: bool,
+    /// Communication state, with the selector flag (0 SOTDMA, 1 ITDMA) in
+    /// the top bit
+    pub radio: u32,
+}
+
+impl ClassBPositionReport {
+    /// The SOTDMA state, unless the selector flag is set for ITDMA (which
+    /// CS units always set).
+    pub fn sotdma(&self) -> Option<SotdmaState> {
+        (self.radio >> 19 == 0).then(|| SotdmaState::from_raw(self.radio))
+    }
+}
+
+impl Payload for ClassBPositionReport {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let msg_type = r.read_u(6)? as u8;
+        if msg_type != 18 {
+            return Err(DecodeError::MessageType(msg_type));
+        }
+        let repeat = r.read_u(2)? as u8;
+        let mmsi = Mmsi(r.read_u(30)? as u32);
+        r.skip(8)?;
+        let speed = SpeedOverGround(r.read_u(10)? as u16);
+        let accuracy = r.read_bool()?;
+        let position = Position::decode(r)?;
+        let course = CourseOverGround(r.read_u(12)? as u16);
+        let heading = Heading(r.read_u(9)? as u16);
+        let timestamp = r.read_u(6)? as u8;
+        let regional = r.read_u(2)? as u8;
+        let cs_unit = r.read_bool()?;
+        let display = r.read_bool()?;
+        let dsc = r.read_bool()?;
+        let band = r.read_bool()?;
+        let msg22 = r.read_bool()?;
+        let assigned = r.read_bool()?;
+        let raim = r.read_bool()?;
+        let radio = r.read_u(20)? as u32;
+        Ok(ClassBPositionReport {
+            repeat,
+            mmsi,
+            speed,
+            accuracy,
+            position,
+            course,
+            heading,
+            timestamp,
+            regional,
+            cs_unit,
+            display,
+            dsc,
+            band,
+            msg22,
+            assigned,
+            raim,
+            radio,
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(6, 18);
+        w.write_u(2, self.repeat.into());
+        w.write_u(30, self.mmsi.into());
+        w.write_u(8, 0);
+        w.write_u(10, self.speed.0.into());
+        w.write_bool(self.accuracy);
+        self.position.encode(w);
+        w.write_u(12, self.course.0.into());
+        w.write_u(9, self.heading.0.into());
+        w.write_u(6, self.timestamp.into());
+        w.write_u(2, self.regional.into());
+        w.write_bool(self.cs_unit);
+        w.write_bool(self.display);
+        w.write_bool(self.dsc);
+        w.write_bool(self.band);
+        w.write_bool(self.msg22);
+        w.write_bool(self.assigned);
+        w.write_bool(self.raim);
+        w.write_u(20, self.radio.into());
+    }
+}
+
+/// Extended Class B position report, message type 19, which adds the
+/// static data of types 5 and 24.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ExtendedClassBReport {
+    pub repeat: u8,
+    pub mmsi: Mmsi,
+    pub speed: SpeedOverGround,
+    pub accuracy: bool,
+    pub position: Position,
+    pub course: CourseOverGround,
+    pub heading: Heading,
+    /// UTC second when the report was generated, 60-63 if unavailable
+    pub timestamp: u8,
+    /// Reserved for regional applications
+    pub regional: u8,
+    /// As transmitted, padded with '@'. See [`super::trim_text`].
+    pub name: String,
+    pub ship_type: u8,
+    pub dimensions: Dimensions,
+    pub epfd: EpfdType,
+    pub raim: bool,
+    /// Data terminal equipment not ready
+    pub dte: bool,
+    /// Station is in assigned mode
+    pub assigned: bool,
+}
+
+impl Payload for ExtendedClassBReport {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let msg_type = r.read_u(6)? as u8;
+        if msg_type != 19 {
+            return Err(DecodeError::MessageType(msg_type));
+        }
+        let repeat = r.read_u(2)? as u8;
+        let mmsi = Mmsi(r.read_u(30)? as u32);
+        r.skip(8)?;
+        let speed = SpeedOverGround(r.read_u(10)? as u16);
+        let accuracy = r.read_bool()?;
+        let position = Position::decode(r)?;
+        let course = CourseOverGround(r.read_u(12)? as u16);
+        let heading = Heading(r.read_u(9)? as u16);
+        let timestamp = r.read_u(6)? as u8;
+        let regional = r.read_u(4)? as u8;
+        let name = r.read_str(20)?;
+        let ship_type = r.read_u(8)? as u8;
+        let dimensions = Dimensions::decode(r)?;
+        let epfd = EpfdType::from(r.read_u(4)? as u8);
+        let raim = r.read_bool()?;
+        let dte = r.read_bool()?;
+        let assigned = r.read_bool()?;
+        r.skip(4)?;
+        Ok(ExtendedClassBReport {
+            repeat,
+            mmsi,
+            speed,
+            accuracy,
+            position,
+            course,
+            heading,
+            timestamp,
+            regional,
+            name,
+            ship_type,
+            dimensions,
+            epfd,
+            raim,
+            dte,
+            assigned,
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(6, 19);
+        w.write_u(2, self.repeat.into());
+        w.write_u(30, self.mmsi.into());
+        w.write_u(8, 0);
+        w.write_u(10, self.speed.0.into());
+        w.write_bool(self.accuracy);
+        self.position.encode(w);
+        w.write_u(12, self.course.0.into());
+        w.write_u(9, self.heading.0.into());
+        w.write_u(6, self.timestamp.into());
+        w.write_u(4, self.regional.into());
+        w.write_str(20, &self.name);
+        w.write_u(8, self.ship_type.into());
+        self.dimensions.encode(w);
+        w.write_u(4, u8::from(self.epfd).into());
+        w.write_bool(self.raim);
+        w.write_bool(self.dte);
+        w.write_bool(self.assigned);
+        w.write_u(4, 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::{Message, trim_text};
+
+    #[test]
+    fn test_decode_type_18() {
+        let (data, drop_bits, _) = crate::armor::unpack("B5NJ;PP005l4ot5Isbl03wsUkP06", 0).unwrap();
+        let m = ClassBPositionReport::from_bits(&data, drop_bits).unwrap();
+        assert_eq!(m.mmsi, Mmsi(367430530));
+        assert_eq!(m.speed.knots(), Some(0.0));
+        assert!(!m.accuracy);
+        assert_eq!(m.position.longitude(), Some(-122.26732));
+        assert_eq!(m.position.latitude(), Some(37.785035));
+        assert_eq!(m.course.degrees(), Some(0.0));
+        assert_eq!(m.heading, Heading::NOT_AVAILABLE);
+        assert_eq!(m.timestamp, 55);
+        assert!(m.cs_unit);
+        assert!(!m.display);
+        assert!(m.dsc);
+        assert!(m.band);
+        assert!(m.msg22);
+        assert!(!m.assigned);
+        assert!(!m.raim);
+        // CS units send a fixed value with the ITDMA selector set
+        assert_eq!(m.radio, 917510);
+        assert_eq!(m.sotdma(), None);
+
+        assert_eq!(m.to_bits(), (data.clone(), drop_bits));
+        assert!(matches!(
+            Message::from_bits(&data, drop_bits).unwrap(),
+            Message::ClassBPositionReport(_)
+        ));
+    }
+
+    #[test]
+    fn test_type_19_round_trip() {
+        let m = ExtendedClassBReport {
+            repeat: 0,
+            mmsi: Mmsi(367059850),
+            speed: SpeedOverGround::from_knots(8.7),
+            accuracy: false,
+            position: Position::from_degrees(-88.810392, 29.543695),
+            course: CourseOverGround::from_degrees(335.9),
+            heading: Heading::NOT_AVAILABLE,
+            timestamp: 46,
+            regional: 0,
+            name: "CAPT.J.RIMES".into(),
+            ship_type: 70,
+            dimensions: Dimensions {
+                to_bow: 5,
+                to_stern: 21,
+                to_port: 4,
+                to_starboard: 4,
+            },
+            epfd: EpfdType::Gps,
+            raim: false,
+            dte: false,
+            assigned: false,
+        };
+        let (data, drop_bits) = m.to_bits();
+        assert_eq!(data.len() * 8 - usize::from(drop_bits.value()), 312);
+
+        let decoded = ExtendedClassBReport::from_bits(&data, drop_bits).unwrap();
+        assert_eq!(decoded.name, "CAPT.J.RIMES@@@@@@@@");
+        assert_eq!(trim_text(&decoded.name), "CAPT.J.RIMES");
+        assert_eq!(decoded.dimensions.length(), 26);
+        assert_eq!(decoded.dimensions.beam(), 8);
+        assert_eq!(
+            ExtendedClassBReport {
+                name: m.name.clone(),
+                ..decoded.clone()
+            },
+            m
+        );
+        assert_eq!(decoded.to_bits(), (data.clone(), drop_bits));
+        assert!(matches!(
+            Message::from_bits(&data, drop_bits).unwrap(),
+            Message::ExtendedClassBReport(_)
+        ));
+    }
+}
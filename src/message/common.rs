@@ -0,0 +1,321 @@
+use crate::bits::{BitReader, BitWriter, Overrun};
+
+/// Longitude and latitude in 1/10000 minute, as carried by most position
+/// reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Position {
+    pub lon: i32,
+    pub lat: i32,
+}
+
+impl Position {
+    /// 181° longitude and 91° latitude signal "not available"
+    pub const NOT_AVAILABLE: Position = Position {
+        lon: 181 * 600_000,
+        lat: 91 * 600_000,
+    };
+
+    pub fn from_degrees(lon: f64, lat: f64) -> Self {
+        Position {
+            lon: (lon * 600_000.0).round() as i32,
+            lat: (lat * 600_000.0).round() as i32,
+        }
+    }
+
+    pub fn longitude(&self) -> Option<f64> {
+        (self.lon != Self::NOT_AVAILABLE.lon).then(|| f64::from(self.lon) / 600_000.0)
+    }
+
+    pub fn latitude(&self) -> Option<f64> {
+        (self.lat != Self::NOT_AVAILABLE.lat).then(|| f64::from(self.lat) / 600_000.0)
+    }
+
+    pub(crate) fn decode(r: &mut BitReader) -> Result<Self, Overrun> {
+        let lon = r.read_i(28)? as i32;
+        let lat = r.read_i(27)? as i32;
+        Ok(Position { lon, lat })
+    }
+
+    pub(crate) fn encode(&self, w: &mut BitWriter) {
+        w.write_i(28, self.lon.into());
+        w.write_i(27, self.lat.into());
+    }
+
+    /// Decodes a lower resolution position, where each unit is `step`
+    /// 1/10000 minutes, e.g. 10 for the 1/1000 minute fields of IMO 289.
+    pub(crate) fn decode_coarse(
+        r: &mut BitReader,
+        lon_bits: usize,
+        lat_bits: usize,
+        step: i32,
+    ) -> Result<Self, Overrun> {
+        let lon = r.read_i(lon_bits)? as i32 * step;
+        let lat = r.read_i(lat_bits)? as i32 * step;
+        Ok(Position { lon, lat })
+    }
+
+    /// Encodes at a lower resolution, rounding towards zero.
+    pub(crate) fn encode_coarse(
+        &self,
+        w: &mut BitWriter,
+        lon_bits: usize,
+        lat_bits: usize,
+        step: i32,
+    ) {
+        w.write_i(lon_bits, (self.lon / step).into());
+        w.write_i(lat_bits, (self.lat / step).into());
+    }
+}
+
+/// Speed over ground in 1/10 knot. 1022 means 102.2 knots or higher.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SpeedOverGround(pub u16);
+
+impl SpeedOverGround {
+    pub const NOT_AVAILABLE: SpeedOverGround = SpeedOverGround(1023);
+
+    pub fn from_knots(knots: f64) -> Self {
+        SpeedOverGround((knots * 10.0).round().clamp(0.0, 1022.0) as u16)
+    }
+
+    pub fn knots(&self) -> Option<f64> {
+        (*self != Self::NOT_AVAILABLE).then(|| f64::from(self.0) / 10.0)
+    }
+}
+
+/// Course over ground in 1/10 degree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CourseOverGround(pub u16);
+
+impl CourseOverGround {
+    pub const NOT_AVAILABLE: CourseOverGround = CourseOverGround(3600);
+
+    pub fn from_degrees(degrees: f64) -> Self {
+        CourseOverGround(((degrees * 10.0).round() as i64).rem_euclid(3600) as u16)
+    }
+
+    pub fn degrees(&self) -> Option<f64> {
+        (self.0 < 3600).then(|| f64::from(self.0) / 10.0)
+    }
+}
+
+/// True heading in whole degrees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Heading(pub u16);
+
+impl Heading {
+    pub const NOT_AVAILABLE: Heading = Heading(511);
+
+    pub fn degrees(&self) -> Option<u16> {
+        (self.0 < 360).then_some(self.0)
+    }
+}
+
+/// Type of electronic position fixing device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum EpfdType {
+    Undefined,
+    Gps,
+    Glonass,
+    CombinedGpsGlonass,
+    LoranC,
+    Chayka,
+    IntegratedNavigation,
+    Surveyed,
+    Galileo,
+    InternalGnss,
+    /// 9 to 14 are reserved
+    Reserved(u8),
+}
+
+impl From<u8> for EpfdType {
+    fn from(x: u8) -> Self {
+        match x {
+            0 => EpfdType::Undefined,
+            1 => EpfdType::Gps,
+            2 => EpfdType::Glonass,
+            3 => EpfdType::CombinedGpsGlonass,
+            4 => EpfdType::LoranC,
+            5 => EpfdType::Chayka,
+            6 => EpfdType::IntegratedNavigation,
+            7 => EpfdType::Surveyed,
+            8 => EpfdType::Galileo,
+            15 => EpfdType::InternalGnss,
+            x => EpfdType::Reserved(x),
+        }
+    }
+}
+
+impl From<EpfdType> for u8 {
+    fn from(t: EpfdType) -> Self {
+        match t {
+            EpfdType::Undefined => 0,
+            EpfdType::Gps => 1,
+            EpfdType::Glonass => 2,
+            EpfdType::CombinedGpsGlonass => 3,
+            EpfdType::LoranC => 4,
+            EpfdType::Chayka => 5,
+            EpfdType::IntegratedNavigation => 6,
+            EpfdType::Surveyed => 7,
+            EpfdType::Galileo => 8,
+            EpfdType::InternalGnss => 15,
+            EpfdType::Reserved(x) => x,
+        }
+    }
+}
+
+/// The 19-bit SOTDMA communication state. What the sub message carries
+/// depends on the slot timeout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SotdmaState {
+    /// 0 UTC direct, 1 UTC indirect, 2 synchronised to a base station, 3
+    /// synchronised to another station
+    pub sync_state: u8,
+    /// Frames left until a new slot is selected
+    pub slot_timeout: u8,
+    pub sub_message: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum SotdmaSubMessage {
+    /// Slot timeout 0: offset to the slot used next
+    SlotOffset(u16),
+    /// Slot timeout 1
+    UtcHourMinute { hour: u8, minute: u8 },
+    /// Slot timeout 2, 4 or 6: slot number used for this transmission
+    SlotNumber(u16),
+    /// Slot timeout 3, 5 or 7: number of other stations received
+    ReceivedStations(u16),
+}
+
+impl SotdmaState {
+    pub fn from_raw(raw: u32) -> Self {
+        SotdmaState {
+            sync_state: ((raw >> 17) & 0x3) as u8,
+            slot_timeout: ((raw >> 14) & 0x7) as u8,
+            sub_message: (raw & 0x3fff) as u16,
+        }
+    }
+
+    pub fn raw(&self) -> u32 {
+        (u32::from(self.sync_state & 0x3) << 17)
+            | (u32::from(self.slot_timeout & 0x7) << 14)
+            | u32::from(self.sub_message & 0x3fff)
+    }
+
+    pub fn sub_message(&self) -> SotdmaSubMessage {
+        match self.slot_timeout {
+            0 => SotdmaSubMessage::SlotOffset(self.sub_message),
+            1 => SotdmaSubMessage::UtcHourMinute {
+                hour: (self.sub_message >> 9) as u8 & 0x1f,
+                minute: (self.sub_message >> 2) as u8 & 0x7f,
+            },
+            2 | 4 | 6 => SotdmaSubMessage::SlotNumber(self.sub_message),
+            _ => SotdmaSubMessage::ReceivedStations(self.sub_message),
+        }
+    }
+}
+
+/// Distances in metres from the position reference point to the bow,
+/// stern, port and starboard. Values of 511 and 63 mean that far or more.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Dimensions {
+    pub to_bow: u16,
+    pub to_stern: u16,
+    pub to_port: u8,
+    pub to_starboard: u8,
+}
+
+impl Dimensions {
+    pub fn length(&self) -> u16 {
+        self.to_bow + self.to_stern
+    }
+
+    pub fn beam(&self) -> u8 {
+        self.to_port + self.to_starboard
+    }
+
+    pub(crate) fn decode(r: &mut BitReader) -> Result<Self, Overrun> {
+        Ok(Dimensions {
+            to_bow: r.read_u(9)? as u16,
+            to_stern: r.read_u(9)? as u16,
+            to_port: r.read_u(6)? as u8,
+            to_starboard: r.read_u(6)? as u8,
+        })
+    }
+
+    pub(crate) fn encode(&self, w: &mut BitWriter) {
+        w.write_u(9, self.to_bow.into());
+        w.write_u(9, self.to_stern.into());
+        w.write_u(6, self.to_port.into());
+        w.write_u(6, self.to_starboard.into());
+    }
+}
+
+/// A time without a year, as used for ETAs and notice start times. Month 0,
+/// day 0, hour 24 and minute 60 mean not available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MonthDayTime {
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+}
+
+impl MonthDayTime {
+    pub const NOT_AVAILABLE: MonthDayTime = MonthDayTime {
+        month: 0,
+        day: 0,
+        hour: 24,
+        minute: 60,
+    };
+
+    pub(crate) fn decode(r: &mut BitReader) -> Result<Self, Overrun> {
+        Ok(MonthDayTime {
+            month: r.read_u(4)? as u8,
+            day: r.read_u(5)? as u8,
+            hour: r.read_u(5)? as u8,
+            minute: r.read_u(6)? as u8,
+        })
+    }
+
+    pub(crate) fn encode(&self, w: &mut BitWriter) {
+        w.write_u(4, self.month.into());
+        w.write_u(5, self.day.into());
+        w.write_u(5, self.hour.into());
+        w.write_u(6, self.minute.into());
+    }
+}
+
+/// Strips the '@' padding and trailing spaces from six-bit text fields.
+pub fn trim_text(s: &str) -> &str {
+    s.trim_end_matches(['@', ' '])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_course_from_degrees() {
+        assert_eq!(
+            CourseOverGround::from_degrees(335.9),
+            CourseOverGround(3359)
+        );
+        assert_eq!(CourseOverGround::from_degrees(360.0), CourseOverGround(0));
+        assert_eq!(CourseOverGround::from_degrees(-10.0).degrees(), Some(350.0));
+        assert_eq!(
+            CourseOverGround::from_degrees(-370.5),
+            CourseOverGround(3495)
+        );
+    }
+}
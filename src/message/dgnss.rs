@@ -0,0 +1,99 @@
+use bit_struct::u3;
+
+use super::{DecodeError, Payload, Position};
+use crate::bits::{BitReader, BitWriter};
+use crate::mmsi::Mmsi;
+
+/// GNSS broadcast binary message, type 17: differential corrections from a
+/// reference station, as ITU-R M.823 words.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DgnssBroadcast {
+    pub repeat: u8,
+    pub mmsi: Mmsi,
+    /// The reference station, sent at 1/10 minute resolution
+    pub position: Position,
+    /// Left-aligned, with `drop_bits` unused bits at the end. Empty when
+    /// the station has no corrections to send.
+    pub data: Vec<u8>,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::bits::serialize_u3"))]
+    pub drop_bits: u3,
+}
+
+impl DgnssBroadcast {
+    /// Reference station ID, from the header of the M.823 message. None if
+    /// there's no correction data.
+    pub fn station_id(&self) -> Option<u16> {
+        let mut r = BitReader::new(&self.data, self.drop_bits);
+        // After the 6-bit M.823 message type
+        r.skip(6).ok()?;
+        r.read_u(10).ok().map(|id| id as u16)
+    }
+}
+
+impl Payload for DgnssBroadcast {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let msg_type = r.read_u(6)? as u8;
+        if msg_type != 17 {
+            return Err(DecodeError::MessageType(msg_type));
+        }
+        let repeat = r.read_u(2)? as u8;
+        let mmsi = Mmsi(r.read_u(30)? as u32);
+        r.skip(2)?;
+        let position = Position::decode_coarse(r, 18, 17, 1000)?;
+        r.skip(5)?;
+        let (data, drop_bits) = r.read_bits(r.remaining())?;
+        Ok(DgnssBroadcast {
+            repeat,
+            mmsi,
+            position,
+            data,
+            drop_bits,
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(6, 17);
+        w.write_u(2, self.repeat.into());
+        w.write_u(30, self.mmsi.into());
+        w.write_u(2, 0);
+        self.position.encode_coarse(w, 18, 17, 1000);
+        w.write_u(5, 0);
+        let bits = self.data.len() * 8 - usize::from(self.drop_bits.value());
+        w.write_bits(&self.data, bits);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::Message;
+
+    #[test]
+    fn test_decode_type_17() {
+        let (data, drop_bits, _) = crate::armor::unpack(
+            "A02VqLPA4I6C07h5Ed1h<OrsuBTTwS?r:C?w`?la<gno1RTRwSP9:BcurA8a",
+            0,
+        )
+        .unwrap();
+        let m = DgnssBroadcast::from_bits(&data, drop_bits).unwrap();
+        assert_eq!(m.mmsi, Mmsi(2734450));
+        // 29.13 E, 59.987 N, sent in 1/10 minutes
+        assert_eq!(m.position.lon, 17478 * 1000);
+        assert_eq!(m.position.lat, 35992 * 1000);
+        assert_eq!(m.station_id(), Some(5));
+        assert_eq!(m.data.len(), 35);
+        assert_eq!(m.drop_bits.value(), 0);
+        assert_eq!(m.to_bits(), (data.clone(), drop_bits));
+        assert!(matches!(
+            Message::from_bits(&data, drop_bits).unwrap(),
+            Message::DgnssBroadcast(_)
+        ));
+
+        let empty = DgnssBroadcast {
+            data: Vec::new(),
+            ..m
+        };
+        assert_eq!(empty.station_id(), None);
+    }
+}
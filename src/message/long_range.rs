@@ -0,0 +1,101 @@
+use super::{DecodeError, NavigationStatus, Payload, Position};
+use crate::bits::{BitReader, BitWriter};
+use crate::mmsi::Mmsi;
+
+/// Position report for long-range (satellite) reception, message type 27.
+/// Position, speed and course are all coarser than in types 1-3.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LongRangeReport {
+    pub repeat: u8,
+    pub mmsi: Mmsi,
+    pub accuracy: bool,
+    pub raim: bool,
+    pub status: NavigationStatus,
+    /// Sent at 1/10 minute resolution
+    pub position: Position,
+    /// Whole knots, 63 if not available
+    pub speed: u8,
+    /// Whole degrees, 511 if not available
+    pub course: u16,
+    /// Position isn't from the current GNSS fix
+    pub not_current: bool,
+}
+
+impl Payload for LongRangeReport {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let msg_type = r.read_u(6)? as u8;
+        if msg_type != 27 {
+            return Err(DecodeError::MessageType(msg_type));
+        }
+        let repeat = r.read_u(2)? as u8;
+        let mmsi = Mmsi(r.read_u(30)? as u32);
+        let accuracy = r.read_bool()?;
+        let raim = r.read_bool()?;
+        let status = NavigationStatus::from(r.read_u(4)? as u8);
+        let position = Position::decode_coarse(r, 18, 17, 1000)?;
+        let speed = r.read_u(6)? as u8;
+        let course = r.read_u(9)? as u16;
+        let not_current = r.read_bool()?;
+        r.skip(1)?;
+        Ok(LongRangeReport {
+            repeat,
+            mmsi,
+            accuracy,
+            raim,
+            status,
+            position,
+            speed,
+            course,
+            not_current,
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(6, 27);
+        w.write_u(2, self.repeat.into());
+        w.write_u(30, self.mmsi.into());
+        w.write_bool(self.accuracy);
+        w.write_bool(self.raim);
+        w.write_u(4, u8::from(self.status).into());
+        self.position.encode_coarse(w, 18, 17, 1000);
+        w.write_u(6, self.speed.into());
+        w.write_u(9, self.course.into());
+        w.write_bool(self.not_current);
+        w.write_u(1, 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::Message;
+
+    #[test]
+    fn test_type_27_round_trip() {
+        let m = LongRangeReport {
+            repeat: 3,
+            mmsi: Mmsi(236091959),
+            accuracy: false,
+            raim: false,
+            status: NavigationStatus::UnderWayUsingEngine,
+            position: Position::from_degrees(-7.5, 36.25),
+            speed: 12,
+            course: 511,
+            not_current: false,
+        };
+        let (data, drop_bits) = m.to_bits();
+        assert_eq!((data.len(), drop_bits.value()), (12, 0));
+        assert_eq!(
+            Message::from_bits(&data, drop_bits).unwrap(),
+            Message::LongRangeReport(m.clone())
+        );
+
+        // Finer positions are truncated to 1/10 minute
+        let fine = LongRangeReport {
+            position: Position::from_degrees(-7.50001, 36.25001),
+            ..m.clone()
+        };
+        assert_eq!(fine.to_bits(), (data, drop_bits));
+    }
+}
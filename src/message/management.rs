@@ -0,0 +1,579 @@
+//! Link management messages, which base stations and other stations use to
+//! request data and control how stations transmit.
+
+use super::{DecodeError, Payload, Position};
+use crate::bits::{BitReader, BitWriter};
+use crate::mmsi::Mmsi;
+
+/// Pads to a byte boundary, as the variable length messages here do.
+fn pad(w: &mut BitWriter) {
+    w.write_u((8 - w.len() % 8) % 8, 0);
+}
+
+/// UTC/date inquiry, message type 10, answered with a type 11.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct UtcInquiry {
+    pub repeat: u8,
+    pub mmsi: Mmsi,
+    pub dest_mmsi: Mmsi,
+}
+
+impl Payload for UtcInquiry {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let msg_type = r.read_u(6)? as u8;
+        if msg_type != 10 {
+            return Err(DecodeError::MessageType(msg_type));
+        }
+        let repeat = r.read_u(2)? as u8;
+        let mmsi = Mmsi(r.read_u(30)? as u32);
+        r.skip(2)?;
+        let dest_mmsi = Mmsi(r.read_u(30)? as u32);
+        r.skip(2)?;
+        Ok(UtcInquiry {
+            repeat,
+            mmsi,
+            dest_mmsi,
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(6, 10);
+        w.write_u(2, self.repeat.into());
+        w.write_u(30, self.mmsi.into());
+        w.write_u(2, 0);
+        w.write_u(30, self.dest_mmsi.into());
+        w.write_u(2, 0);
+    }
+}
+
+/// A message type requested by an interrogation, and the slot offset to
+/// reply in (0 for the responder to choose).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Request {
+    pub msg_type: u8,
+    pub offset: u16,
+}
+
+impl Request {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        Ok(Request {
+            msg_type: r.read_u(6)? as u8,
+            offset: r.read_u(12)? as u16,
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(6, self.msg_type.into());
+        w.write_u(12, self.offset.into());
+    }
+}
+
+/// Interrogation, message type 15: one or two requests to a first station
+/// and optionally one to a second.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Interrogation {
+    pub repeat: u8,
+    pub mmsi: Mmsi,
+    pub dest_mmsi: Mmsi,
+    pub request: Request,
+    pub second_request: Option<Request>,
+    /// Sending this without `second_request` writes an empty one, which
+    /// decodes as `Some`.
+    pub second_station: Option<(Mmsi, Request)>,
+}
+
+impl Payload for Interrogation {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let msg_type = r.read_u(6)? as u8;
+        if msg_type != 15 {
+            return Err(DecodeError::MessageType(msg_type));
+        }
+        let repeat = r.read_u(2)? as u8;
+        let mmsi = Mmsi(r.read_u(30)? as u32);
+        r.skip(2)?;
+        let dest_mmsi = Mmsi(r.read_u(30)? as u32);
+        let request = Request::decode(r)?;
+        let mut second_request = None;
+        let mut second_station = None;
+        // 88, 110 or 160 bits, give or take padding
+        if r.remaining() >= 2 + 18 {
+            r.skip(2)?;
+            second_request = Some(Request::decode(r)?);
+            if r.remaining() >= 2 + 48 {
+                r.skip(2)?;
+                second_station = Some((Mmsi(r.read_u(30)? as u32), Request::decode(r)?));
+            }
+        }
+        Ok(Interrogation {
+            repeat,
+            mmsi,
+            dest_mmsi,
+            request,
+            second_request,
+            second_station,
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(6, 15);
+        w.write_u(2, self.repeat.into());
+        w.write_u(30, self.mmsi.into());
+        w.write_u(2, 0);
+        w.write_u(30, self.dest_mmsi.into());
+        self.request.encode(w);
+        if self.second_request.is_some() || self.second_station.is_some() {
+            w.write_u(2, 0);
+            let empty = Request {
+                msg_type: 0,
+                offset: 0,
+            };
+            self.second_request.unwrap_or(empty).encode(w);
+            w.write_u(2, 0);
+        }
+        if let Some((mmsi, request)) = &self.second_station {
+            w.write_u(30, (*mmsi).into());
+            request.encode(w);
+            w.write_u(2, 0);
+        }
+    }
+}
+
+/// A slot assignment: transmit from slot `offset`, then every `increment`
+/// slots. An increment of 0 means a single transmission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Assignment {
+    pub mmsi: Mmsi,
+    pub offset: u16,
+    pub increment: u16,
+}
+
+/// Assigned mode command, message type 16, for one or two stations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct AssignmentCommand {
+    pub repeat: u8,
+    pub mmsi: Mmsi,
+    pub assignments: Vec<Assignment>,
+}
+
+impl Payload for AssignmentCommand {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let msg_type = r.read_u(6)? as u8;
+        if msg_type != 16 {
+            return Err(DecodeError::MessageType(msg_type));
+        }
+        let repeat = r.read_u(2)? as u8;
+        let mmsi = Mmsi(r.read_u(30)? as u32);
+        r.skip(2)?;
+        let mut assignments = Vec::new();
+        while assignments.len() < 2 && (assignments.is_empty() || r.remaining() >= 52) {
+            assignments.push(Assignment {
+                mmsi: Mmsi(r.read_u(30)? as u32),
+                offset: r.read_u(12)? as u16,
+                increment: r.read_u(10)? as u16,
+            });
+        }
+        Ok(AssignmentCommand {
+            repeat,
+            mmsi,
+            assignments,
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(6, 16);
+        w.write_u(2, self.repeat.into());
+        w.write_u(30, self.mmsi.into());
+        w.write_u(2, 0);
+        for a in self.assignments.iter().take(2) {
+            w.write_u(30, a.mmsi.into());
+            w.write_u(12, a.offset.into());
+            w.write_u(10, a.increment.into());
+        }
+        pad(w);
+    }
+}
+
+/// Slots reserved by a base station for its own use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Reservation {
+    pub offset: u16,
+    /// Number of consecutive slots
+    pub slots: u8,
+    /// Minutes
+    pub timeout: u8,
+    pub increment: u16,
+}
+
+/// Data link management, message type 20, with up to four reservations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DataLinkManagement {
+    pub repeat: u8,
+    pub mmsi: Mmsi,
+    pub reservations: Vec<Reservation>,
+}
+
+impl Payload for DataLinkManagement {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let msg_type = r.read_u(6)? as u8;
+        if msg_type != 20 {
+            return Err(DecodeError::MessageType(msg_type));
+        }
+        let repeat = r.read_u(2)? as u8;
+        let mmsi = Mmsi(r.read_u(30)? as u32);
+        r.skip(2)?;
+        let mut reservations = Vec::new();
+        while reservations.len() < 4 && (reservations.is_empty() || r.remaining() >= 30) {
+            reservations.push(Reservation {
+                offset: r.read_u(12)? as u16,
+                slots: r.read_u(4)? as u8,
+                timeout: r.read_u(3)? as u8,
+                increment: r.read_u(11)? as u16,
+            });
+        }
+        Ok(DataLinkManagement {
+            repeat,
+            mmsi,
+            reservations,
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(6, 20);
+        w.write_u(2, self.repeat.into());
+        w.write_u(30, self.mmsi.into());
+        w.write_u(2, 0);
+        for res in self.reservations.iter().take(4) {
+            w.write_u(12, res.offset.into());
+            w.write_u(4, res.slots.into());
+            w.write_u(3, res.timeout.into());
+            w.write_u(11, res.increment.into());
+        }
+        pad(w);
+    }
+}
+
+/// A rectangle given by its north-east and south-west corners, sent at
+/// 1/10 minute resolution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Area {
+    pub ne: Position,
+    pub sw: Position,
+}
+
+impl Area {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        Ok(Area {
+            ne: Position::decode_coarse(r, 18, 17, 1000)?,
+            sw: Position::decode_coarse(r, 18, 17, 1000)?,
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        self.ne.encode_coarse(w, 18, 17, 1000);
+        self.sw.encode_coarse(w, 18, 17, 1000);
+    }
+}
+
+/// Who a channel management message applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ChannelTarget {
+    Area(Area),
+    /// One or two stations, the second 0 if unused
+    Addressed(Mmsi, Mmsi),
+}
+
+/// Channel management, message type 22.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ChannelManagement {
+    pub repeat: u8,
+    pub mmsi: Mmsi,
+    pub channel_a: u16,
+    pub channel_b: u16,
+    /// 0 Tx A and B, Rx A and B; 1 Tx A, Rx A and B; 2 Tx B, Rx A and B
+    pub tx_rx: u8,
+    /// Low power
+    pub power: bool,
+    pub target: ChannelTarget,
+    /// 12.5 kHz bandwidth on channel A
+    pub band_a: bool,
+    pub band_b: bool,
+    /// Transitional zone size in nautical miles, minus one
+    pub zone_size: u8,
+}
+
+impl Payload for ChannelManagement {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let msg_type = r.read_u(6)? as u8;
+        if msg_type != 22 {
+            return Err(DecodeError::MessageType(msg_type));
+        }
+        let repeat = r.read_u(2)? as u8;
+        let mmsi = Mmsi(r.read_u(30)? as u32);
+        r.skip(2)?;
+        let channel_a = r.read_u(12)? as u16;
+        let channel_b = r.read_u(12)? as u16;
+        let tx_rx = r.read_u(4)? as u8;
+        let power = r.read_bool()?;
+        // Only the flag after the target says how to read it
+        let mut target = r.clone();
+        r.skip(70)?;
+        let addressed = r.read_bool()?;
+        let target = if addressed {
+            let first = Mmsi(target.read_u(30)? as u32);
+            target.skip(5)?;
+            ChannelTarget::Addressed(first, Mmsi(target.read_u(30)? as u32))
+        } else {
+            ChannelTarget::Area(Area::decode(&mut target)?)
+        };
+        let band_a = r.read_bool()?;
+        let band_b = r.read_bool()?;
+        let zone_size = r.read_u(3)? as u8;
+        r.skip(23)?;
+        Ok(ChannelManagement {
+            repeat,
+            mmsi,
+            channel_a,
+            channel_b,
+            tx_rx,
+            power,
+            target,
+            band_a,
+            band_b,
+            zone_size,
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(6, 22);
+        w.write_u(2, self.repeat.into());
+        w.write_u(30, self.mmsi.into());
+        w.write_u(2, 0);
+        w.write_u(12, self.channel_a.into());
+        w.write_u(12, self.channel_b.into());
+        w.write_u(4, self.tx_rx.into());
+        w.write_bool(self.power);
+        match self.target {
+            ChannelTarget::Area(area) => area.encode(w),
+            ChannelTarget::Addressed(first, second) => {
+                w.write_u(30, first.into());
+                w.write_u(5, 0);
+                w.write_u(30, second.into());
+                w.write_u(5, 0);
+            }
+        }
+        w.write_bool(matches!(self.target, ChannelTarget::Addressed(..)));
+        w.write_bool(self.band_a);
+        w.write_bool(self.band_b);
+        w.write_u(3, self.zone_size.into());
+        w.write_u(23, 0);
+    }
+}
+
+/// Group assignment command, message type 23, for stations of a given
+/// type within an area.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GroupAssignment {
+    pub repeat: u8,
+    pub mmsi: Mmsi,
+    pub area: Area,
+    /// 0 all mobile stations, 1 Class A, 2 all Class B, 3 SAR aircraft, 4
+    /// Class B SO, 5 Class B CS, 6 inland waterways, 7-9 regional, 10-15
+    /// reserved
+    pub station_type: u8,
+    /// Ship and cargo type, 0 for all
+    pub ship_type: u8,
+    /// As for [`ChannelManagement::tx_rx`]
+    pub tx_rx: u8,
+    /// Reporting interval code, from ITU-R M.1371 table 77
+    pub interval: u8,
+    /// Minutes to stay quiet, 0 for none
+    pub quiet: u8,
+}
+
+impl Payload for GroupAssignment {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let msg_type = r.read_u(6)? as u8;
+        if msg_type != 23 {
+            return Err(DecodeError::MessageType(msg_type));
+        }
+        let repeat = r.read_u(2)? as u8;
+        let mmsi = Mmsi(r.read_u(30)? as u32);
+        r.skip(2)?;
+        let area = Area::decode(r)?;
+        let station_type = r.read_u(4)? as u8;
+        let ship_type = r.read_u(8)? as u8;
+        r.skip(22)?;
+        let tx_rx = r.read_u(2)? as u8;
+        let interval = r.read_u(4)? as u8;
+        let quiet = r.read_u(4)? as u8;
+        r.skip(6)?;
+        Ok(GroupAssignment {
+            repeat,
+            mmsi,
+            area,
+            station_type,
+            ship_type,
+            tx_rx,
+            interval,
+            quiet,
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(6, 23);
+        w.write_u(2, self.repeat.into());
+        w.write_u(30, self.mmsi.into());
+        w.write_u(2, 0);
+        self.area.encode(w);
+        w.write_u(4, self.station_type.into());
+        w.write_u(8, self.ship_type.into());
+        w.write_u(22, 0);
+        w.write_u(2, self.tx_rx.into());
+        w.write_u(4, self.interval.into());
+        w.write_u(4, self.quiet.into());
+        w.write_u(6, 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::Message;
+
+    fn round_trip(m: impl Into<Message> + Clone, bits: usize) {
+        let m = m.into();
+        let (data, drop_bits) = m.to_bits();
+        assert_eq!(data.len() * 8 - usize::from(drop_bits.value()), bits);
+        assert_eq!(Message::from_bits(&data, drop_bits).unwrap(), m);
+    }
+
+    #[test]
+    fn test_round_trips() {
+        round_trip(
+            UtcInquiry {
+                repeat: 0,
+                mmsi: Mmsi(2655651),
+                dest_mmsi: Mmsi(2191000),
+            },
+            72,
+        );
+
+        let request = Request {
+            msg_type: 5,
+            offset: 0,
+        };
+        let interrogation = Interrogation {
+            repeat: 0,
+            mmsi: Mmsi(2442000),
+            dest_mmsi: Mmsi(244670316),
+            request,
+            second_request: None,
+            second_station: None,
+        };
+        round_trip(interrogation.clone(), 88);
+        let two = Interrogation {
+            second_request: Some(Request {
+                msg_type: 24,
+                offset: 10,
+            }),
+            ..interrogation.clone()
+        };
+        round_trip(two.clone(), 110);
+        round_trip(
+            Interrogation {
+                second_station: Some((Mmsi(244123456), request)),
+                ..two
+            },
+            160,
+        );
+
+        let assignment = Assignment {
+            mmsi: Mmsi(244670316),
+            offset: 200,
+            increment: 0,
+        };
+        round_trip(
+            AssignmentCommand {
+                repeat: 0,
+                mmsi: Mmsi(2442000),
+                assignments: vec![assignment],
+            },
+            96,
+        );
+        round_trip(
+            AssignmentCommand {
+                repeat: 0,
+                mmsi: Mmsi(2442000),
+                assignments: vec![assignment; 2],
+            },
+            144,
+        );
+
+        let reservation = Reservation {
+            offset: 2049,
+            slots: 5,
+            timeout: 7,
+            increment: 225,
+        };
+        for (count, bits) in [(1, 72), (2, 104), (3, 136), (4, 160)] {
+            round_trip(
+                DataLinkManagement {
+                    repeat: 0,
+                    mmsi: Mmsi(3669145),
+                    reservations: vec![reservation; count],
+                },
+                bits,
+            );
+        }
+
+        let area = Area {
+            ne: Position::from_degrees(4.5, 52.5),
+            sw: Position::from_degrees(3.0, 51.0),
+        };
+        let channels = ChannelManagement {
+            repeat: 0,
+            mmsi: Mmsi(2442000),
+            channel_a: 2087,
+            channel_b: 2088,
+            tx_rx: 0,
+            power: false,
+            target: ChannelTarget::Area(area),
+            band_a: false,
+            band_b: false,
+            zone_size: 4,
+        };
+        round_trip(channels.clone(), 168);
+        round_trip(
+            ChannelManagement {
+                target: ChannelTarget::Addressed(Mmsi(244670316), Mmsi(0)),
+                ..channels
+            },
+            168,
+        );
+
+        round_trip(
+            GroupAssignment {
+                repeat: 0,
+                mmsi: Mmsi(2442000),
+                area,
+                station_type: 5,
+                ship_type: 0,
+                tx_rx: 0,
+                interval: 9,
+                quiet: 0,
+            },
+            160,
+        );
+    }
+}
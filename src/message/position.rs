@@ -0,0 +1,188 @@
+use super::{CourseOverGround, DecodeError, Heading, Payload, Position, SpeedOverGround};
+use crate::bits::{BitReader, BitWriter};
+use crate::mmsi::Mmsi;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum NavigationStatus {
+    UnderWayUsingEngine,
+    AtAnchor,
+    NotUnderCommand,
+    RestrictedManoeuvrability,
+    ConstrainedByDraught,
+    Moored,
+    Aground,
+    EngagedInFishing,
+    UnderWaySailing,
+    AisSartActive,
+    NotDefined,
+    /// 9 to 13 are reserved for future amendments
+    Reserved(u8),
+}
+
+impl From<u8> for NavigationStatus {
+    fn from(x: u8) -> Self {
+        match x {
+            0 => NavigationStatus::UnderWayUsingEngine,
+            1 => NavigationStatus::AtAnchor,
+            2 => NavigationStatus::NotUnderCommand,
+            3 => NavigationStatus::RestrictedManoeuvrability,
+            4 => NavigationStatus::ConstrainedByDraught,
+            5 => NavigationStatus::Moored,
+            6 => NavigationStatus::Aground,
+            7 => NavigationStatus::EngagedInFishing,
+            8 => NavigationStatus::UnderWaySailing,
+            14 => NavigationStatus::AisSartActive,
+            15 => NavigationStatus::NotDefined,
+            x => NavigationStatus::Reserved(x),
+        }
+    }
+}
+
+impl From<NavigationStatus> for u8 {
+    fn from(s: NavigationStatus) -> Self {
+        match s {
+            NavigationStatus::UnderWayUsingEngine => 0,
+            NavigationStatus::AtAnchor => 1,
+            NavigationStatus::NotUnderCommand => 2,
+            NavigationStatus::RestrictedManoeuvrability => 3,
+            NavigationStatus::ConstrainedByDraught => 4,
+            NavigationStatus::Moored => 5,
+            NavigationStatus::Aground => 6,
+            NavigationStatus::EngagedInFishing => 7,
+            NavigationStatus::UnderWaySailing => 8,
+            NavigationStatus::AisSartActive => 14,
+            NavigationStatus::NotDefined => 15,
+            NavigationStatus::Reserved(x) => x,
+        }
+    }
+}
+
+/// Rate of turn as transmitted, i.e. 4.733 * sqrt(degrees per minute).
+/// -128 means not available, ±127 turning faster than 5° per 30s with no
+/// turn indicator available.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RateOfTurn(pub i8);
+
+impl RateOfTurn {
+    pub const NOT_AVAILABLE: RateOfTurn = RateOfTurn(-128);
+
+    /// Degrees per minute, if the indicator reading is available
+    pub fn degrees_per_minute(&self) -> Option<f64> {
+        match self.0 {
+            -128 | -127 | 127 => None,
+            x => {
+                let rot = f64::from(x) / 4.733;
+                Some(rot * rot.abs())
+            }
+        }
+    }
+}
+
+/// Class A position report, message types 1, 2 and 3.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PositionReport {
+    pub msg_type: u8,
+    pub repeat: u8,
+    pub mmsi: Mmsi,
+    pub status: NavigationStatus,
+    pub rate_of_turn: RateOfTurn,
+    pub speed: SpeedOverGround,
+    pub accuracy: bool,
+    pub position: Position,
+    pub course: CourseOverGround,
+    pub heading: Heading,
+    /// UTC second when the report was generated, 60-63 if unavailable
+    pub timestamp: u8,
+    pub maneuver: u8,
+    pub raim: bool,
+    pub radio: u32,
+}
+
+impl Payload for PositionReport {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let msg_type = r.read_u(6)? as u8;
+        if !(1..=3).contains(&msg_type) {
+            return Err(DecodeError::MessageType(msg_type));
+        }
+        let repeat = r.read_u(2)? as u8;
+        let mmsi = Mmsi(r.read_u(30)? as u32);
+        let status = NavigationStatus::from(r.read_u(4)? as u8);
+        let rate_of_turn = RateOfTurn(r.read_i(8)? as i8);
+        let speed = SpeedOverGround(r.read_u(10)? as u16);
+        let accuracy = r.read_bool()?;
+        let position = Position::decode(r)?;
+        let course = CourseOverGround(r.read_u(12)? as u16);
+        let heading = Heading(r.read_u(9)? as u16);
+        let timestamp = r.read_u(6)? as u8;
+        let maneuver = r.read_u(2)? as u8;
+        r.skip(3)?;
+        let raim = r.read_bool()?;
+        let radio = r.read_u(19)? as u32;
+        Ok(PositionReport {
+            msg_type,
+            repeat,
+            mmsi,
+            status,
+            rate_of_turn,
+            speed,
+            accuracy,
+            position,
+            course,
+            heading,
+            timestamp,
+            maneuver,
+            raim,
+            radio,
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(6, self.msg_type.into());
+        w.write_u(2, self.repeat.into());
+        w.write_u(30, self.mmsi.into());
+        w.write_u(4, u8::from(self.status).into());
+        w.write_i(8, self.rate_of_turn.0.into());
+        w.write_u(10, self.speed.0.into());
+        w.write_bool(self.accuracy);
+        self.position.encode(w);
+        w.write_u(12, self.course.0.into());
+        w.write_u(9, self.heading.0.into());
+        w.write_u(6, self.timestamp.into());
+        w.write_u(2, self.maneuver.into());
+        w.write_u(3, 0);
+        w.write_bool(self.raim);
+        w.write_u(19, self.radio.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_type_1() {
+        let (data, drop_bits, _) = crate::armor::unpack("13HOI:0P0000VOHLCnHQKwvL05Ip", 0).unwrap();
+        let m = PositionReport::from_bits(&data, drop_bits).unwrap();
+        assert_eq!(m.msg_type, 1);
+        assert_eq!(m.mmsi, Mmsi(227006760));
+        assert_eq!(m.status, NavigationStatus::UnderWayUsingEngine);
+        assert_eq!(m.rate_of_turn, RateOfTurn::NOT_AVAILABLE);
+        assert_eq!(m.speed.knots(), Some(0.0));
+        assert_eq!(
+            m.position,
+            Position {
+                lon: 78828,
+                lat: 29685346
+            }
+        );
+        assert_eq!(m.course.degrees(), Some(36.7));
+        assert_eq!(m.heading.degrees(), None);
+        assert_eq!(m.timestamp, 14);
+        assert_eq!(m.radio, 22136);
+
+        assert_eq!(m.to_bits(), (data, drop_bits));
+    }
+}
@@ -0,0 +1,136 @@
+use super::{DecodeError, Payload};
+use crate::bits::{BitReader, BitWriter};
+use crate::mmsi::Mmsi;
+
+fn read_text(r: &mut BitReader) -> Result<String, DecodeError> {
+    // Any bits short of a whole character are padding
+    Ok(r.read_str(r.remaining() / 6)?)
+}
+
+fn write_text(w: &mut BitWriter, text: &str) {
+    w.write_str(text.chars().count(), text);
+}
+
+/// Addressed safety-related message, type 12.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SafetyAddressedMessage {
+    pub repeat: u8,
+    pub mmsi: Mmsi,
+    pub sequence: u8,
+    pub dest_mmsi: Mmsi,
+    pub retransmit: bool,
+    pub text: String,
+}
+
+impl Payload for SafetyAddressedMessage {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let msg_type = r.read_u(6)? as u8;
+        if msg_type != 12 {
+            return Err(DecodeError::MessageType(msg_type));
+        }
+        let repeat = r.read_u(2)? as u8;
+        let mmsi = Mmsi(r.read_u(30)? as u32);
+        let sequence = r.read_u(2)? as u8;
+        let dest_mmsi = Mmsi(r.read_u(30)? as u32);
+        let retransmit = r.read_bool()?;
+        r.skip(1)?;
+        let text = read_text(r)?;
+        Ok(SafetyAddressedMessage {
+            repeat,
+            mmsi,
+            sequence,
+            dest_mmsi,
+            retransmit,
+            text,
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(6, 12);
+        w.write_u(2, self.repeat.into());
+        w.write_u(30, self.mmsi.into());
+        w.write_u(2, self.sequence.into());
+        w.write_u(30, self.dest_mmsi.into());
+        w.write_bool(self.retransmit);
+        w.write_u(1, 0);
+        write_text(w, &self.text);
+    }
+}
+
+/// Broadcast safety-related message, type 14.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SafetyBroadcastMessage {
+    pub repeat: u8,
+    pub mmsi: Mmsi,
+    pub text: String,
+}
+
+impl Payload for SafetyBroadcastMessage {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let msg_type = r.read_u(6)? as u8;
+        if msg_type != 14 {
+            return Err(DecodeError::MessageType(msg_type));
+        }
+        let repeat = r.read_u(2)? as u8;
+        let mmsi = Mmsi(r.read_u(30)? as u32);
+        r.skip(2)?;
+        let text = read_text(r)?;
+        Ok(SafetyBroadcastMessage { repeat, mmsi, text })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(6, 14);
+        w.write_u(2, self.repeat.into());
+        w.write_u(30, self.mmsi.into());
+        w.write_u(2, 0);
+        write_text(w, &self.text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::Message;
+
+    #[test]
+    fn test_decode_type_14() {
+        let (data, drop_bits, _) = crate::armor::unpack(">5?Per18=HB1U:1@E=B0m<L", 2).unwrap();
+        let m = SafetyBroadcastMessage::from_bits(&data, drop_bits).unwrap();
+        assert_eq!(m.mmsi, Mmsi(351809000));
+        assert_eq!(m.text, "RCVD YR TEST MSG");
+        assert_eq!(m.to_bits(), (data.clone(), drop_bits));
+        assert!(matches!(
+            Message::from_bits(&data, drop_bits).unwrap(),
+            Message::SafetyBroadcastMessage(_)
+        ));
+    }
+
+    #[test]
+    fn test_type_12_round_trip() {
+        let m = SafetyAddressedMessage {
+            repeat: 0,
+            mmsi: Mmsi(271002099),
+            sequence: 0,
+            dest_mmsi: Mmsi(271002111),
+            retransmit: true,
+            text: "MSG FROM 271002099".into(),
+        };
+        let (data, drop_bits) = m.to_bits();
+        assert_eq!(
+            SafetyAddressedMessage::from_bits(&data, drop_bits).unwrap(),
+            m
+        );
+
+        // A partial character at the end is padding
+        let mut w = BitWriter::new();
+        m.encode(&mut w);
+        w.write_u(2, 0);
+        let (data, drop_bits) = w.finish();
+        assert_eq!(
+            Message::from_bits(&data, drop_bits).unwrap(),
+            Message::SafetyAddressedMessage(m)
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use super::{CourseOverGround, DecodeError, Payload, Position};
+use crate::bits::{BitReader, BitWriter};
+use crate::mmsi::Mmsi;
+
+/// Standard SAR aircraft position report, message type 9.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SarAircraftReport {
+    pub repeat: u8,
+    pub mmsi: Mmsi,
+    /// Metres, 4094 meaning 4094 or higher and 4095 not available
+    pub altitude: u16,
+    /// Whole knots, unlike the 1/10 knot of ship reports. 1022 means 1022
+    /// knots or higher and 1023 not available.
+    pub speed: u16,
+    pub accuracy: bool,
+    pub position: Position,
+    pub course: CourseOverGround,
+    /// UTC second when the report was generated, 60-63 if unavailable
+    pub timestamp: u8,
+    /// Altitude from a barometric sensor rather than GNSS
+    pub barometric: bool,
+    /// Data terminal equipment not ready
+    pub dte: bool,
+    /// Station is in assigned mode
+    pub assigned: bool,
+    pub raim: bool,
+    /// Communication state, with the selector flag (0 SOTDMA, 1 ITDMA) in
+    /// the top bit
+    pub radio: u32,
+}
+
+impl Payload for SarAircraftReport {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let msg_type = r.read_u(6)? as u8;
+        if msg_type != 9 {
+            return Err(DecodeError::MessageType(msg_type));
+        }
+        let repeat = r.read_u(2)? as u8;
+        let mmsi = Mmsi(r.read_u(30)? as u32);
+        let altitude = r.read_u(12)? as u16;
+        let speed = r.read_u(10)? as u16;
+        let accuracy = r.read_bool()?;
+        let position = Position::decode(r)?;
+        let course = CourseOverGround(r.read_u(12)? as u16);
+        let timestamp = r.read_u(6)? as u8;
+        let barometric = r.read_bool()?;
+        r.skip(7)?;
+        let dte = r.read_bool()?;
+        r.skip(3)?;
+        let assigned = r.read_bool()?;
+        let raim = r.read_bool()?;
+        let radio = r.read_u(20)? as u32;
+        Ok(SarAircraftReport {
+            repeat,
+            mmsi,
+            altitude,
+            speed,
+            accuracy,
+            position,
+            course,
+            timestamp,
+            barometric,
+            dte,
+            assigned,
+            raim,
+            radio,
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(6, 9);
+        w.write_u(2, self.repeat.into());
+        w.write_u(30, self.mmsi.into());
+        w.write_u(12, self.altitude.into());
+        w.write_u(10, self.speed.into());
+        w.write_bool(self.accuracy);
+        self.position.encode(w);
+        w.write_u(12, self.course.0.into());
+        w.write_u(6, self.timestamp.into());
+        w.write_bool(self.barometric);
+        w.write_u(7, 0);
+        w.write_bool(self.dte);
+        w.write_u(3, 0);
+        w.write_bool(self.assigned);
+        w.write_bool(self.raim);
+        w.write_u(20, self.radio.into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::Message;
+
+    #[test]
+    fn test_decode_type_9() {
+        let (data, drop_bits, _) = crate::armor::unpack("91b55wi;hbOS@OdQAC062Ch2089h", 0).unwrap();
+        let m = SarAircraftReport::from_bits(&data, drop_bits).unwrap();
+        assert_eq!(m.mmsi, Mmsi(111232511));
+        assert_eq!(m.altitude, 303);
+        assert_eq!(m.speed, 42);
+        assert_eq!(m.position.longitude(), Some(-6.2788433333333336));
+        assert_eq!(m.position.latitude(), Some(58.144));
+        assert_eq!(m.course.degrees(), Some(154.5));
+        assert_eq!(m.timestamp, 15);
+        assert!(m.dte);
+        assert!(!m.barometric);
+        assert_eq!(m.to_bits(), (data.clone(), drop_bits));
+        assert!(matches!(
+            Message::from_bits(&data, drop_bits).unwrap(),
+            Message::SarAircraftReport(_)
+        ));
+    }
+}
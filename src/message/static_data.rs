@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+
+use super::{DecodeError, Dimensions, Payload};
+use crate::bits::{BitReader, BitWriter};
+use crate::mmsi::{Mmsi, MmsiKind};
+
+/// Whether `mmsi` belongs to an auxiliary craft associated with a parent
+/// ship (98MIDXXXX), whose type 24 part B carries the mothership's MMSI
+/// instead of dimensions.
+pub fn is_auxiliary(mmsi: Mmsi) -> bool {
+    mmsi.kind() == MmsiKind::AuxiliaryCraft
+}
+
+/// Static data report, message type 24. Each sentence carries one of two
+/// parts, which [`StaticDataCache`] pairs up.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StaticDataReport {
+    pub repeat: u8,
+    pub mmsi: Mmsi,
+    pub part: StaticDataPart,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum StaticDataPart {
+    A(PartA),
+    B(PartB),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PartA {
+    /// As transmitted, padded with '@'. See [`super::trim_text`].
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PartB {
+    pub ship_type: u8,
+    /// Manufacturer's mnemonic
+    pub vendor_id: String,
+    pub model: u8,
+    pub serial: u32,
+    pub call_sign: String,
+    pub reference: Reference,
+}
+
+/// The last 30 bits of part B, depending on whether the sender is an
+/// auxiliary craft.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Reference {
+    Dimensions(Dimensions),
+    Mothership(Mmsi),
+}
+
+impl Payload for StaticDataReport {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let msg_type = r.read_u(6)? as u8;
+        if msg_type != 24 {
+            return Err(DecodeError::MessageType(msg_type));
+        }
+        let repeat = r.read_u(2)? as u8;
+        let mmsi = Mmsi(r.read_u(30)? as u32);
+        let part = match r.read_u(2)? as u8 {
+            0 => {
+                let name = r.read_str(20)?;
+                // Older transponders omit the trailing spare bits
+                if r.remaining() >= 8 {
+                    r.skip(8)?;
+                }
+                StaticDataPart::A(PartA { name })
+            }
+            1 => {
+                let ship_type = r.read_u(8)? as u8;
+                let vendor_id = r.read_str(3)?;
+                let model = r.read_u(4)? as u8;
+                let serial = r.read_u(20)? as u32;
+                let call_sign = r.read_str(7)?;
+                let reference = if is_auxiliary(mmsi) {
+                    Reference::Mothership(Mmsi(r.read_u(30)? as u32))
+                } else {
+                    Reference::Dimensions(Dimensions::decode(r)?)
+                };
+                r.skip(6)?;
+                StaticDataPart::B(PartB {
+                    ship_type,
+                    vendor_id,
+                    model,
+                    serial,
+                    call_sign,
+                    reference,
+                })
+            }
+            // Part numbers 2 and 3 are undefined
+            value => {
+                return Err(DecodeError::Reserved {
+                    field: "part_number",
+                    value,
+                });
+            }
+        };
+        Ok(StaticDataReport { repeat, mmsi, part })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(6, 24);
+        w.write_u(2, self.repeat.into());
+        w.write_u(30, self.mmsi.into());
+        match &self.part {
+            StaticDataPart::A(a) => {
+                w.write_u(2, 0);
+                w.write_str(20, &a.name);
+                w.write_u(8, 0);
+            }
+            StaticDataPart::B(b) => {
+                w.write_u(2, 1);
+                w.write_u(8, b.ship_type.into());
+                w.write_str(3, &b.vendor_id);
+                w.write_u(4, b.model.into());
+                w.write_u(20, b.serial.into());
+                w.write_str(7, &b.call_sign);
+                match b.reference {
+                    Reference::Dimensions(d) => d.encode(w),
+                    Reference::Mothership(mmsi) => w.write_u(30, mmsi.into()),
+                }
+                w.write_u(6, 0);
+            }
+        }
+    }
+}
+
+/// Both parts of a type 24 report for one MMSI.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StaticData {
+    pub mmsi: Mmsi,
+    pub a: PartA,
+    pub b: PartB,
+}
+
+/// Pairs type 24 parts A and B by MMSI. Keeps the latest of each part, so
+/// a change to either part produces an updated record.
+#[derive(Debug, Clone, Default)]
+pub struct StaticDataCache {
+    parts: HashMap<Mmsi, (Option<PartA>, Option<PartB>)>,
+}
+
+impl StaticDataCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a part, returning the complete record if the other part has
+    /// already been seen.
+    pub fn push(&mut self, report: StaticDataReport) -> Option<StaticData> {
+        let entry = self.parts.entry(report.mmsi).or_default();
+        match report.part {
+            StaticDataPart::A(a) => entry.0 = Some(a),
+            StaticDataPart::B(b) => entry.1 = Some(b),
+        }
+        self.get(report.mmsi)
+    }
+
+    pub fn get(&self, mmsi: Mmsi) -> Option<StaticData> {
+        match self.parts.get(&mmsi)? {
+            (Some(a), Some(b)) => Some(StaticData {
+                mmsi,
+                a: a.clone(),
+                b: b.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Number of MMSIs seen, complete or not
+    pub fn len(&self) -> usize {
+        self.parts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::{Message, trim_text};
+
+    fn decode(payload: &str, fill: u8) -> (StaticDataReport, Vec<u8>, bit_struct::u3) {
+        let (data, drop_bits, _) = crate::armor::unpack(payload, fill).unwrap();
+        let m = StaticDataReport::from_bits(&data, drop_bits).unwrap();
+        (m, data, drop_bits)
+    }
+
+    #[test]
+    fn test_decode_parts() {
+        let (a, data, drop_bits) = decode("H42O55i18tMET00000000000000", 2);
+        assert_eq!(a.mmsi, Mmsi(271041815));
+        let StaticDataPart::A(part_a) = &a.part else {
+            panic!("expected part A: {a:?}");
+        };
+        assert_eq!(trim_text(&part_a.name), "PROGUY");
+        // Written back with the spare bits of the current layout
+        let (bits, _) = a.to_bits();
+        assert_eq!(bits.len(), 21);
+        assert_eq!(&bits[..data.len()], &data[..]);
+        assert!(matches!(
+            Message::from_bits(&data, drop_bits).unwrap(),
+            Message::StaticDataReport(_)
+        ));
+
+        let (b, data, drop_bits) = decode("H42O55lti4hhhilD3nink000?050", 0);
+        let StaticDataPart::B(part_b) = &b.part else {
+            panic!("expected part B: {b:?}");
+        };
+        assert_eq!(part_b.ship_type, 60);
+        assert_eq!(trim_text(&part_b.call_sign), "TC6163");
+        assert_eq!(
+            part_b.reference,
+            Reference::Dimensions(Dimensions {
+                to_bow: 0,
+                to_stern: 15,
+                to_port: 0,
+                to_starboard: 5
+            })
+        );
+        assert_eq!(b.to_bits(), (data, drop_bits));
+
+        let mut cache = StaticDataCache::new();
+        assert_eq!(cache.push(b.clone()), None);
+        let record = cache.push(a.clone()).unwrap();
+        assert_eq!(record.mmsi, Mmsi(271041815));
+        assert_eq!(&record.a, part_a);
+        assert_eq!(&record.b, part_b);
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_auxiliary() {
+        let m = StaticDataReport {
+            repeat: 0,
+            mmsi: Mmsi(982_326_789),
+            part: StaticDataPart::B(PartB {
+                ship_type: 37,
+                vendor_id: "ABC".into(),
+                model: 2,
+                serial: 12345,
+                call_sign: "TENDER1".into(),
+                reference: Reference::Mothership(Mmsi(232_678_900)),
+            }),
+        };
+        assert!(is_auxiliary(m.mmsi));
+        assert!(!is_auxiliary(Mmsi(232_678_900)));
+        let (data, drop_bits) = m.to_bits();
+        assert_eq!(data.len() * 8 - usize::from(drop_bits.value()), 168);
+        assert_eq!(StaticDataReport::from_bits(&data, drop_bits).unwrap(), m);
+
+        let mut cache = StaticDataCache::new();
+        assert_eq!(cache.push(m.clone()), None);
+        assert_eq!(cache.get(m.mmsi), None);
+        let a = StaticDataReport {
+            repeat: 0,
+            mmsi: m.mmsi,
+            part: StaticDataPart::A(PartA {
+                name: "TENDER".into(),
+            }),
+        };
+        let record = cache.push(a).unwrap();
+        assert_eq!(record.b.reference, Reference::Mothership(Mmsi(232_678_900)));
+    }
+}
@@ -0,0 +1,129 @@
+use super::{DecodeError, Dimensions, EpfdType, MonthDayTime, Payload};
+use crate::bits::{BitReader, BitWriter};
+use crate::mmsi::Mmsi;
+
+/// Static and voyage related data, message type 5. Usually split across two
+/// sentences.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StaticVoyageData {
+    pub repeat: u8,
+    pub mmsi: Mmsi,
+    /// 0 for ITU-R M.1371-1, up to 3 for later editions
+    pub ais_version: u8,
+    pub imo: u32,
+    /// As transmitted, padded with '@'. See [`super::trim_text`].
+    pub call_sign: String,
+    /// As transmitted, padded with '@'
+    pub name: String,
+    pub ship_type: u8,
+    pub dimensions: Dimensions,
+    pub epfd: EpfdType,
+    pub eta: MonthDayTime,
+    /// 1/10 metre
+    pub draught: u8,
+    /// As transmitted, padded with '@'
+    pub destination: String,
+    /// Data terminal equipment not ready
+    pub dte: bool,
+}
+
+impl Payload for StaticVoyageData {
+    fn decode(r: &mut BitReader) -> Result<Self, DecodeError> {
+        let msg_type = r.read_u(6)? as u8;
+        if msg_type != 5 {
+            return Err(DecodeError::MessageType(msg_type));
+        }
+        let repeat = r.read_u(2)? as u8;
+        let mmsi = Mmsi(r.read_u(30)? as u32);
+        let ais_version = r.read_u(2)? as u8;
+        let imo = r.read_u(30)? as u32;
+        let call_sign = r.read_str(7)?;
+        let name = r.read_str(20)?;
+        let ship_type = r.read_u(8)? as u8;
+        let dimensions = Dimensions::decode(r)?;
+        let epfd = EpfdType::from(r.read_u(4)? as u8);
+        let eta = MonthDayTime::decode(r)?;
+        let draught = r.read_u(8)? as u8;
+        let destination = r.read_str(20)?;
+        let dte = r.read_bool()?;
+        // Some transmitters drop the final spare bit
+        if r.remaining() > 0 {
+            r.skip(1)?;
+        }
+        Ok(StaticVoyageData {
+            repeat,
+            mmsi,
+            ais_version,
+            imo,
+            call_sign,
+            name,
+            ship_type,
+            dimensions,
+            epfd,
+            eta,
+            draught,
+            destination,
+            dte,
+        })
+    }
+
+    fn encode(&self, w: &mut BitWriter) {
+        w.write_u(6, 5);
+        w.write_u(2, self.repeat.into());
+        w.write_u(30, self.mmsi.into());
+        w.write_u(2, self.ais_version.into());
+        w.write_u(30, self.imo.into());
+        w.write_str(7, &self.call_sign);
+        w.write_str(20, &self.name);
+        w.write_u(8, self.ship_type.into());
+        self.dimensions.encode(w);
+        w.write_u(4, u8::from(self.epfd).into());
+        self.eta.encode(w);
+        w.write_u(8, self.draught.into());
+        w.write_str(20, &self.destination);
+        w.write_bool(self.dte);
+        w.write_u(1, 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::{Message, trim_text};
+
+    #[test]
+    fn test_decode_type_5() {
+        let body = concat!(
+            "55?MbV02;H;s<HtKR20EHE:0@T4@Dn2222222216L961O5Gf0NSQEp6ClRp8",
+            "88888888880"
+        );
+        let (data, drop_bits, _) = crate::armor::unpack(body, 2).unwrap();
+        let m = StaticVoyageData::from_bits(&data, drop_bits).unwrap();
+        assert_eq!(m.mmsi, Mmsi(351759000));
+        assert_eq!(m.imo, 9134270);
+        assert_eq!(trim_text(&m.call_sign), "3FOF8");
+        assert_eq!(trim_text(&m.name), "EVER DIADEM");
+        assert_eq!(m.ship_type, 70);
+        assert_eq!(m.dimensions.length(), 295);
+        assert_eq!(m.dimensions.beam(), 32);
+        assert_eq!(m.epfd, EpfdType::Gps);
+        assert_eq!(
+            m.eta,
+            MonthDayTime {
+                month: 5,
+                day: 15,
+                hour: 14,
+                minute: 0
+            }
+        );
+        assert_eq!(m.draught, 122);
+        assert_eq!(trim_text(&m.destination), "NEW YORK");
+        assert!(!m.dte);
+        assert_eq!(m.to_bits(), (data.clone(), drop_bits));
+        assert!(matches!(
+            Message::from_bits(&data, drop_bits).unwrap(),
+            Message::StaticVoyageData(_)
+        ));
+    }
+}
@@ -0,0 +1,435 @@
+//! Plausibility checks on decoded messages. Decoding is deliberately
+//! lenient, so these report what a strict reading of ITU-R M.1371 would
+//! reject without throwing the message away.
+
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use bit_struct::u3;
+
+use super::{
+    AidType, CourseOverGround, DecodeError, EpfdType, Heading, Message, MonthDayTime,
+    NavigationStatus, Position, UtcDateTime,
+};
+use crate::bits::BitReader;
+use crate::mmsi::Mmsi;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    /// The payload isn't a valid length for its type. Payloads too short
+    /// to decode at all are kept as [`Message::Unknown`].
+    Length {
+        bits: usize,
+        expected: RangeInclusive<usize>,
+    },
+    /// A field holds a value outside its range, in transmitted units
+    OutOfRange { field: &'static str, value: i64 },
+    /// A field holds a value reserved for future use
+    Reserved { field: &'static str, value: i64 },
+    /// The source MMSI doesn't fit any of the formats of ITU-R M.585, or
+    /// has a MID that isn't allocated
+    InvalidMmsi(Mmsi),
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Issue::Length { bits, expected } if expected.start() == expected.end() => {
+                write!(fmt, "{bits} bits, expected {}", expected.start())
+            }
+            Issue::Length { bits, expected } => write!(
+                fmt,
+                "{bits} bits, expected {} to {}",
+                expected.start(),
+                expected.end()
+            ),
+            Issue::OutOfRange { field, value } => write!(fmt, "{field} out of range: {value}"),
+            Issue::Reserved { field, value } => write!(fmt, "{field} uses reserved value {value}"),
+            Issue::InvalidMmsi(mmsi) => write!(fmt, "invalid MMSI {mmsi}"),
+        }
+    }
+}
+
+/// Valid payload lengths in bits for each message type.
+pub fn expected_bits(msg_type: u8) -> Option<RangeInclusive<usize>> {
+    let bits = match msg_type {
+        1..=4 | 9 | 11 | 18 | 22 => 168..=168,
+        // Some transmitters leave off the final spare bit. Anything shorter
+        // cuts into the DTE flag.
+        5 => 423..=424,
+        6 => 88..=1008,
+        7 | 13 => 72..=168,
+        8 => 56..=1008,
+        10 => 72..=72,
+        12 => 72..=1008,
+        14 => 40..=1008,
+        15 => 88..=160,
+        16 => 96..=144,
+        17 => 80..=816,
+        19 => 312..=312,
+        20 => 72..=160,
+        21 => 272..=360,
+        23 => 160..=160,
+        // Part A was 160 bits before M.1371-5
+        24 => 160..=168,
+        25 => 40..=168,
+        26 => 60..=1064,
+        27 => 96..=96,
+        _ => return None,
+    };
+    Some(bits)
+}
+
+impl Message {
+    /// Decodes like [`super::Payload::from_bits`], but also returns every issue
+    /// found. Payloads too short for their type become
+    /// [`Message::Unknown`] with a length issue, and those using a reserved
+    /// layout with a reserved value issue, so this only fails if there
+    /// isn't even a message type.
+    pub fn from_bits_validated(
+        data: &[u8],
+        drop_bits: u3,
+    ) -> Result<(Message, Vec<Issue>), DecodeError> {
+        let mut r = BitReader::new(data, drop_bits);
+        let bits = r.len();
+        let msg_type = r.clone().read_u(6)? as u8;
+        let mut issues = Vec::new();
+        if let Some(expected) = expected_bits(msg_type)
+            && !expected.contains(&bits)
+        {
+            issues.push(Issue::Length { bits, expected });
+        }
+        let message = match Message::decode_typed(&mut r) {
+            Ok(message) => message,
+            Err(e) => {
+                if let DecodeError::Reserved { field, value } = e {
+                    issues.push(Issue::Reserved {
+                        field,
+                        value: value.into(),
+                    });
+                }
+                Message::decode_unknown(&mut BitReader::new(data, drop_bits))?
+            }
+        };
+        issues.extend(message.validate());
+        Ok((message, issues))
+    }
+
+    /// Checks the decoded fields. Length can only be checked against the
+    /// original payload, by [`Message::from_bits_validated`].
+    pub fn validate(&self) -> Vec<Issue> {
+        let mut v = Validator::default();
+        if let Some(mmsi) = self.mmsi() {
+            v.mmsi(mmsi);
+        }
+        match self {
+            Message::PositionReport(m) => {
+                v.status(m.status);
+                v.position(&m.position);
+                v.course(m.course);
+                v.heading(m.heading);
+            }
+            Message::BaseStationReport(m) => {
+                v.utc(&m.time);
+                v.position(&m.position);
+                v.epfd(m.epfd);
+            }
+            Message::StaticVoyageData(m) => {
+                v.epfd(m.epfd);
+                v.month_day_time(&m.eta);
+            }
+            Message::SarAircraftReport(m) => {
+                v.position(&m.position);
+                v.course(m.course);
+            }
+            Message::DgnssBroadcast(m) => v.position(&m.position),
+            Message::ClassBPositionReport(m) => {
+                v.position(&m.position);
+                v.course(m.course);
+                v.heading(m.heading);
+            }
+            Message::ExtendedClassBReport(m) => {
+                v.position(&m.position);
+                v.course(m.course);
+                v.heading(m.heading);
+                v.epfd(m.epfd);
+            }
+            Message::AidToNavigationReport(m) => {
+                if let AidType::Reserved(x) = m.aid_type {
+                    v.reserved("aid_type", x.into());
+                }
+                v.position(&m.position);
+                v.epfd(m.epfd);
+            }
+            Message::ChannelManagement(m) => {
+                if let super::ChannelTarget::Area(area) = m.target {
+                    v.position(&area.ne);
+                    v.position(&area.sw);
+                }
+            }
+            Message::GroupAssignment(m) => {
+                v.position(&m.area.ne);
+                v.position(&m.area.sw);
+            }
+            Message::LongRangeReport(m) => {
+                v.status(m.status);
+                v.position(&m.position);
+                if m.course > 359 && m.course != 511 {
+                    v.out_of_range("course", m.course.into());
+                }
+            }
+            _ => {}
+        }
+        v.issues
+    }
+}
+
+#[derive(Debug, Default)]
+struct Validator {
+    issues: Vec<Issue>,
+}
+
+impl Validator {
+    fn out_of_range(&mut self, field: &'static str, value: i64) {
+        self.issues.push(Issue::OutOfRange { field, value });
+    }
+
+    fn reserved(&mut self, field: &'static str, value: i64) {
+        self.issues.push(Issue::Reserved { field, value });
+    }
+
+    fn mmsi(&mut self, mmsi: Mmsi) {
+        if !mmsi.is_valid() {
+            self.issues.push(Issue::InvalidMmsi(mmsi));
+        }
+    }
+
+    fn position(&mut self, p: &Position) {
+        if p.lon.abs() > 180 * 600_000 && p.lon != Position::NOT_AVAILABLE.lon {
+            self.out_of_range("longitude", p.lon.into());
+        }
+        if p.lat.abs() > 90 * 600_000 && p.lat != Position::NOT_AVAILABLE.lat {
+            self.out_of_range("latitude", p.lat.into());
+        }
+    }
+
+    fn course(&mut self, c: CourseOverGround) {
+        if c.0 > CourseOverGround::NOT_AVAILABLE.0 {
+            self.out_of_range("course", c.0.into());
+        }
+    }
+
+    fn heading(&mut self, h: Heading) {
+        if h.0 > 359 && h != Heading::NOT_AVAILABLE {
+            self.out_of_range("heading", h.0.into());
+        }
+    }
+
+    fn status(&mut self, s: NavigationStatus) {
+        if let NavigationStatus::Reserved(x) = s {
+            self.reserved("status", x.into());
+        }
+    }
+
+    fn epfd(&mut self, e: EpfdType) {
+        if let EpfdType::Reserved(x) = e {
+            self.reserved("epfd", x.into());
+        }
+    }
+
+    fn utc(&mut self, t: &UtcDateTime) {
+        self.month_day_time(&MonthDayTime {
+            month: t.month,
+            day: t.day,
+            hour: t.hour,
+            minute: t.minute,
+        });
+        if t.second > 60 {
+            self.out_of_range("second", t.second.into());
+        }
+    }
+
+    fn month_day_time(&mut self, t: &MonthDayTime) {
+        let fields = [
+            ("month", t.month, 12),
+            ("day", t.day, 31),
+            ("hour", t.hour, 24),
+            ("minute", t.minute, 60),
+        ];
+        for (field, value, max) in fields {
+            if value > max {
+                self.out_of_range(field, value.into());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bits::BitWriter;
+    use crate::message::{Payload, PositionReport, SpeedOverGround};
+
+    fn report() -> PositionReport {
+        let (data, drop_bits, _) = crate::armor::unpack("13HOI:0P0000VOHLCnHQKwvL05Ip", 0).unwrap();
+        PositionReport::from_bits(&data, drop_bits).unwrap()
+    }
+
+    #[test]
+    fn test_valid() {
+        let (data, drop_bits) = report().to_bits();
+        let (message, issues) = Message::from_bits_validated(&data, drop_bits).unwrap();
+        assert_eq!(message, Message::PositionReport(report()));
+        assert_eq!(issues, []);
+    }
+
+    #[test]
+    fn test_fields() {
+        let m = PositionReport {
+            mmsi: Mmsi(1_000_000),
+            status: NavigationStatus::Reserved(11),
+            speed: SpeedOverGround::NOT_AVAILABLE,
+            position: Position {
+                lon: Position::NOT_AVAILABLE.lon,
+                lat: -95 * 600_000,
+            },
+            course: CourseOverGround(3601),
+            heading: Heading(400),
+            ..report()
+        };
+        let (data, drop_bits) = m.to_bits();
+        let (_, issues) = Message::from_bits_validated(&data, drop_bits).unwrap();
+        assert_eq!(
+            issues,
+            [
+                Issue::InvalidMmsi(Mmsi(1_000_000)),
+                Issue::Reserved {
+                    field: "status",
+                    value: 11
+                },
+                Issue::OutOfRange {
+                    field: "latitude",
+                    value: -57_000_000
+                },
+                Issue::OutOfRange {
+                    field: "course",
+                    value: 3601
+                },
+                Issue::OutOfRange {
+                    field: "heading",
+                    value: 400
+                },
+            ]
+        );
+        assert_eq!(issues[2].to_string(), "latitude out of range: -57000000");
+    }
+
+    #[test]
+    fn test_length() {
+        // Trailing garbage is decoded but reported
+        let mut w = BitWriter::new();
+        report().encode(&mut w);
+        w.write_u(6, 0);
+        let (data, drop_bits) = w.finish();
+        let (message, issues) = Message::from_bits_validated(&data, drop_bits).unwrap();
+        assert!(matches!(message, Message::PositionReport(_)));
+        assert_eq!(
+            issues,
+            [Issue::Length {
+                bits: 174,
+                expected: 168..=168
+            }]
+        );
+        assert_eq!(issues[0].to_string(), "174 bits, expected 168");
+
+        // Too short to decode
+        let (data, drop_bits) = report().to_bits();
+        let (message, issues) = Message::from_bits_validated(&data[..10], drop_bits).unwrap();
+        assert!(matches!(message, Message::Unknown { msg_type: 1, .. }));
+        assert_eq!(
+            issues,
+            [Issue::Length {
+                bits: 80,
+                expected: 168..=168
+            }]
+        );
+
+        assert!(Message::from_bits_validated(&[], drop_bits).is_err());
+    }
+
+    #[test]
+    fn test_static_voyage_length() {
+        let payload = |bits: usize| {
+            let mut w = BitWriter::new();
+            w.write_u(6, 5);
+            w.write_u(2, 0);
+            w.write_u(30, 227006760);
+            let mut left = bits - 38;
+            while left > 0 {
+                let n = left.min(64);
+                w.write_u(n, 0);
+                left -= n;
+            }
+            w.finish()
+        };
+
+        let (data, drop_bits) = payload(423);
+        let (message, issues) = Message::from_bits_validated(&data, drop_bits).unwrap();
+        assert!(matches!(message, Message::StaticVoyageData(_)));
+        assert!(!issues.iter().any(|i| matches!(i, Issue::Length { .. })));
+
+        let (data, drop_bits) = payload(420);
+        let (message, issues) = Message::from_bits_validated(&data, drop_bits).unwrap();
+        assert!(matches!(message, Message::Unknown { msg_type: 5, .. }));
+        assert_eq!(
+            issues,
+            [Issue::Length {
+                bits: 420,
+                expected: 423..=424
+            }]
+        );
+    }
+
+    #[test]
+    fn test_reserved_layout() {
+        // Type 24 part 3
+        let mut w = BitWriter::new();
+        w.write_u(6, 24);
+        w.write_u(32, 271041815);
+        w.write_u(2, 3);
+        w.write_u(64, 0);
+        w.write_u(64, 0);
+        let (data, drop_bits) = w.finish();
+        let (message, issues) = Message::from_bits_validated(&data, drop_bits).unwrap();
+        assert!(matches!(message, Message::Unknown { msg_type: 24, .. }));
+        assert_eq!(
+            issues,
+            [Issue::Reserved {
+                field: "part_number",
+                value: 3
+            }]
+        );
+    }
+
+    #[test]
+    fn test_mmsi() {
+        for mmsi in [
+            227006760, 2275200, 22751000, 111232511, 970123456, 982326789, 992351001, 123456789,
+        ] {
+            let m = PositionReport {
+                mmsi: Mmsi(mmsi),
+                ..report()
+            };
+            let issues = Message::PositionReport(m).validate();
+            assert_eq!(issues.is_empty(), mmsi != 123456789, "{mmsi}");
+        }
+        for mmsi in [1_000_000_000, 199_000_000, 217_000_001] {
+            let m = PositionReport {
+                mmsi: Mmsi(mmsi),
+                ..report()
+            };
+            let issues = Message::PositionReport(m).validate();
+            assert_eq!(issues, [Issue::InvalidMmsi(Mmsi(mmsi))]);
+        }
+    }
+}
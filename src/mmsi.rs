@@ -0,0 +1,577 @@
+//! Maritime Mobile Service Identities and what they say about a station,
+//! following ITU-R M.585.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// A nine digit MMSI, as carried in every AIS message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Mmsi(pub u32);
+
+/// The kind of station, from the MMSI's format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MmsiKind {
+    /// MIDXXXXXX
+    Ship,
+    /// 0MIDXXXX, addressing a group of ships
+    Group,
+    /// 00MIDXXXX
+    CoastStation,
+    /// 111MIDXXX
+    SarAircraft,
+    /// 8MIDXXXXX
+    Handheld,
+    /// 970XXYYYY
+    AisSart,
+    /// 972XXYYYY
+    ManOverboard,
+    /// 974XXYYYY
+    Epirb,
+    /// 98MIDXXXX, craft associated with a parent ship
+    AuxiliaryCraft,
+    /// 99MIDXXXX
+    AidToNavigation,
+    /// Doesn't fit any format
+    Invalid,
+}
+
+impl MmsiKind {
+    pub const ALL: [MmsiKind; 11] = [
+        MmsiKind::Ship,
+        MmsiKind::Group,
+        MmsiKind::CoastStation,
+        MmsiKind::SarAircraft,
+        MmsiKind::Handheld,
+        MmsiKind::AisSart,
+        MmsiKind::ManOverboard,
+        MmsiKind::Epirb,
+        MmsiKind::AuxiliaryCraft,
+        MmsiKind::AidToNavigation,
+        MmsiKind::Invalid,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MmsiKind::Ship => "ship",
+            MmsiKind::Group => "group",
+            MmsiKind::CoastStation => "coast-station",
+            MmsiKind::SarAircraft => "sar-aircraft",
+            MmsiKind::Handheld => "handheld",
+            MmsiKind::AisSart => "ais-sart",
+            MmsiKind::ManOverboard => "man-overboard",
+            MmsiKind::Epirb => "epirb",
+            MmsiKind::AuxiliaryCraft => "auxiliary-craft",
+            MmsiKind::AidToNavigation => "aid-to-navigation",
+            MmsiKind::Invalid => "invalid",
+        }
+    }
+
+    /// Digits before the MID, for kinds that have one
+    fn mid_offset(&self) -> Option<usize> {
+        match self {
+            MmsiKind::Ship => Some(0),
+            MmsiKind::Group | MmsiKind::Handheld => Some(1),
+            MmsiKind::CoastStation | MmsiKind::AuxiliaryCraft | MmsiKind::AidToNavigation => {
+                Some(2)
+            }
+            MmsiKind::SarAircraft => Some(3),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for MmsiKind {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.write_str(self.name())
+    }
+}
+
+impl FromStr for MmsiKind {
+    type Err = anyhow::Error;
+
+    /// Parses the names used by [`MmsiKind::name`], for command line filters.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        MmsiKind::ALL
+            .into_iter()
+            .find(|k| k.name() == s)
+            .ok_or_else(|| anyhow::anyhow!("unknown MMSI kind '{s}'"))
+    }
+}
+
+/// The administration a MID is allocated to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FlagState {
+    pub mid: u16,
+    /// ISO 3166-1 alpha-2 code. Territories with their own MID have their
+    /// own code where one exists.
+    pub code: &'static str,
+    pub name: &'static str,
+}
+
+impl Mmsi {
+    pub fn kind(&self) -> MmsiKind {
+        let digits = self.digits();
+        match digits.as_bytes() {
+            [_, _, _, _, _, _, _, _, _, _, ..] => MmsiKind::Invalid,
+            [b'0', b'0', ..] => MmsiKind::CoastStation,
+            [b'0', ..] => MmsiKind::Group,
+            [b'1', b'1', b'1', ..] => MmsiKind::SarAircraft,
+            [b'8', ..] => MmsiKind::Handheld,
+            [b'9', b'7', b'0', ..] => MmsiKind::AisSart,
+            [b'9', b'7', b'2', ..] => MmsiKind::ManOverboard,
+            [b'9', b'7', b'4', ..] => MmsiKind::Epirb,
+            [b'9', b'8', ..] => MmsiKind::AuxiliaryCraft,
+            [b'9', b'9', ..] => MmsiKind::AidToNavigation,
+            [b'2'..=b'7', ..] => MmsiKind::Ship,
+            _ => MmsiKind::Invalid,
+        }
+    }
+
+    /// The Maritime Identification Digits, for kinds that carry them.
+    pub fn mid(&self) -> Option<u16> {
+        let offset = self.kind().mid_offset()?;
+        self.digits()[offset..offset + 3].parse().ok()
+    }
+
+    pub fn flag_state(&self) -> Option<FlagState> {
+        flag_state(self.mid()?)
+    }
+
+    /// Whether the MMSI has a valid format, and a MID that's been allocated
+    /// if it should have one.
+    pub fn is_valid(&self) -> bool {
+        match self.kind() {
+            MmsiKind::Invalid => false,
+            kind => kind.mid_offset().is_none() || self.flag_state().is_some(),
+        }
+    }
+
+    fn digits(&self) -> String {
+        format!("{:09}", self.0)
+    }
+}
+
+impl From<u32> for Mmsi {
+    fn from(mmsi: u32) -> Self {
+        Mmsi(mmsi)
+    }
+}
+
+impl From<Mmsi> for u32 {
+    fn from(mmsi: Mmsi) -> Self {
+        mmsi.0
+    }
+}
+
+impl From<Mmsi> for u64 {
+    fn from(mmsi: Mmsi) -> Self {
+        mmsi.0.into()
+    }
+}
+
+/// Serialized with what the number says about the station, e.g.
+/// `{"number":227006760,"kind":"ship","flag_state":{"mid":227,...}}`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Mmsi {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let flag_state = self.flag_state();
+        let mut st = s.serialize_struct("Mmsi", 2 + usize::from(flag_state.is_some()))?;
+        st.serialize_field("number", &self.0)?;
+        st.serialize_field("kind", self.kind().name())?;
+        if let Some(flag_state) = flag_state {
+            st.serialize_field("flag_state", &flag_state)?;
+        } else {
+            st.skip_field("flag_state")?;
+        }
+        st.end()
+    }
+}
+
+impl fmt::Display for Mmsi {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{:09}", self.0)
+    }
+}
+
+impl FromStr for Mmsi {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || s.len() > 9 || !s.bytes().all(|b| b.is_ascii_digit()) {
+            anyhow::bail!("invalid MMSI '{s}', expected up to 9 digits");
+        }
+        Ok(Mmsi(s.parse()?))
+    }
+}
+
+/// Looks up who a MID is allocated to.
+pub fn flag_state(mid: u16) -> Option<FlagState> {
+    let ix = MIDS.binary_search_by_key(&mid, |(m, _, _)| *m).ok()?;
+    let (mid, code, name) = MIDS[ix];
+    Some(FlagState { mid, code, name })
+}
+
+/// Allocated MIDs, sorted, from the ITU's MARS database.
+const MIDS: &[(u16, &str, &str)] = &[
+    (201, "AL", "Albania"),
+    (202, "AD", "Andorra"),
+    (203, "AT", "Austria"),
+    (204, "PT", "Azores"),
+    (205, "BE", "Belgium"),
+    (206, "BY", "Belarus"),
+    (207, "BG", "Bulgaria"),
+    (208, "VA", "Vatican City"),
+    (209, "CY", "Cyprus"),
+    (210, "CY", "Cyprus"),
+    (211, "DE", "Germany"),
+    (212, "CY", "Cyprus"),
+    (213, "GE", "Georgia"),
+    (214, "MD", "Moldova"),
+    (215, "MT", "Malta"),
+    (216, "AM", "Armenia"),
+    (218, "DE", "Germany"),
+    (219, "DK", "Denmark"),
+    (220, "DK", "Denmark"),
+    (224, "ES", "Spain"),
+    (225, "ES", "Spain"),
+    (226, "FR", "France"),
+    (227, "FR", "France"),
+    (228, "FR", "France"),
+    (229, "MT", "Malta"),
+    (230, "FI", "Finland"),
+    (231, "FO", "Faroe Islands"),
+    (232, "GB", "United Kingdom"),
+    (233, "GB", "United Kingdom"),
+    (234, "GB", "United Kingdom"),
+    (235, "GB", "United Kingdom"),
+    (236, "GI", "Gibraltar"),
+    (237, "GR", "Greece"),
+    (238, "HR", "Croatia"),
+    (239, "GR", "Greece"),
+    (240, "GR", "Greece"),
+    (241, "GR", "Greece"),
+    (242, "MA", "Morocco"),
+    (243, "HU", "Hungary"),
+    (244, "NL", "Netherlands"),
+    (245, "NL", "Netherlands"),
+    (246, "NL", "Netherlands"),
+    (247, "IT", "Italy"),
+    (248, "MT", "Malta"),
+    (249, "MT", "Malta"),
+    (250, "IE", "Ireland"),
+    (251, "IS", "Iceland"),
+    (252, "LI", "Liechtenstein"),
+    (253, "LU", "Luxembourg"),
+    (254, "MC", "Monaco"),
+    (255, "PT", "Madeira"),
+    (256, "MT", "Malta"),
+    (257, "NO", "Norway"),
+    (258, "NO", "Norway"),
+    (259, "NO", "Norway"),
+    (261, "PL", "Poland"),
+    (262, "ME", "Montenegro"),
+    (263, "PT", "Portugal"),
+    (264, "RO", "Romania"),
+    (265, "SE", "Sweden"),
+    (266, "SE", "Sweden"),
+    (267, "SK", "Slovakia"),
+    (268, "SM", "San Marino"),
+    (269, "CH", "Switzerland"),
+    (270, "CZ", "Czechia"),
+    (271, "TR", "Türkiye"),
+    (272, "UA", "Ukraine"),
+    (273, "RU", "Russia"),
+    (274, "MK", "North Macedonia"),
+    (275, "LV", "Latvia"),
+    (276, "EE", "Estonia"),
+    (277, "LT", "Lithuania"),
+    (278, "SI", "Slovenia"),
+    (279, "RS", "Serbia"),
+    (301, "AI", "Anguilla"),
+    (303, "US", "Alaska"),
+    (304, "AG", "Antigua and Barbuda"),
+    (305, "AG", "Antigua and Barbuda"),
+    (306, "CW", "Curaçao, Sint Maarten and Caribbean Netherlands"),
+    (307, "AW", "Aruba"),
+    (308, "BS", "Bahamas"),
+    (309, "BS", "Bahamas"),
+    (310, "BM", "Bermuda"),
+    (311, "BS", "Bahamas"),
+    (312, "BZ", "Belize"),
+    (314, "BB", "Barbados"),
+    (316, "CA", "Canada"),
+    (319, "KY", "Cayman Islands"),
+    (321, "CR", "Costa Rica"),
+    (323, "CU", "Cuba"),
+    (325, "DM", "Dominica"),
+    (327, "DO", "Dominican Republic"),
+    (329, "GP", "Guadeloupe"),
+    (330, "GD", "Grenada"),
+    (331, "GL", "Greenland"),
+    (332, "GT", "Guatemala"),
+    (334, "HN", "Honduras"),
+    (336, "HT", "Haiti"),
+    (338, "US", "United States"),
+    (339, "JM", "Jamaica"),
+    (341, "KN", "Saint Kitts and Nevis"),
+    (343, "LC", "Saint Lucia"),
+    (345, "MX", "Mexico"),
+    (347, "MQ", "Martinique"),
+    (348, "MS", "Montserrat"),
+    (350, "NI", "Nicaragua"),
+    (351, "PA", "Panama"),
+    (352, "PA", "Panama"),
+    (353, "PA", "Panama"),
+    (354, "PA", "Panama"),
+    (355, "PA", "Panama"),
+    (356, "PA", "Panama"),
+    (357, "PA", "Panama"),
+    (358, "PR", "Puerto Rico"),
+    (359, "SV", "El Salvador"),
+    (361, "PM", "Saint Pierre and Miquelon"),
+    (362, "TT", "Trinidad and Tobago"),
+    (364, "TC", "Turks and Caicos Islands"),
+    (366, "US", "United States"),
+    (367, "US", "United States"),
+    (368, "US", "United States"),
+    (369, "US", "United States"),
+    (370, "PA", "Panama"),
+    (371, "PA", "Panama"),
+    (372, "PA", "Panama"),
+    (373, "PA", "Panama"),
+    (374, "PA", "Panama"),
+    (375, "VC", "Saint Vincent and the Grenadines"),
+    (376, "VC", "Saint Vincent and the Grenadines"),
+    (377, "VC", "Saint Vincent and the Grenadines"),
+    (378, "VG", "British Virgin Islands"),
+    (379, "VI", "United States Virgin Islands"),
+    (401, "AF", "Afghanistan"),
+    (403, "SA", "Saudi Arabia"),
+    (405, "BD", "Bangladesh"),
+    (408, "BH", "Bahrain"),
+    (410, "BT", "Bhutan"),
+    (412, "CN", "China"),
+    (413, "CN", "China"),
+    (414, "CN", "China"),
+    (416, "TW", "Taiwan"),
+    (417, "LK", "Sri Lanka"),
+    (419, "IN", "India"),
+    (422, "IR", "Iran"),
+    (423, "AZ", "Azerbaijan"),
+    (425, "IQ", "Iraq"),
+    (428, "IL", "Israel"),
+    (431, "JP", "Japan"),
+    (432, "JP", "Japan"),
+    (434, "TM", "Turkmenistan"),
+    (436, "KZ", "Kazakhstan"),
+    (437, "UZ", "Uzbekistan"),
+    (438, "JO", "Jordan"),
+    (440, "KR", "South Korea"),
+    (441, "KR", "South Korea"),
+    (443, "PS", "Palestine"),
+    (445, "KP", "North Korea"),
+    (447, "KW", "Kuwait"),
+    (450, "LB", "Lebanon"),
+    (451, "KG", "Kyrgyzstan"),
+    (453, "MO", "Macao"),
+    (455, "MV", "Maldives"),
+    (457, "MN", "Mongolia"),
+    (459, "NP", "Nepal"),
+    (461, "OM", "Oman"),
+    (463, "PK", "Pakistan"),
+    (466, "QA", "Qatar"),
+    (468, "SY", "Syria"),
+    (470, "AE", "United Arab Emirates"),
+    (471, "AE", "United Arab Emirates"),
+    (472, "TJ", "Tajikistan"),
+    (473, "YE", "Yemen"),
+    (475, "YE", "Yemen"),
+    (477, "HK", "Hong Kong"),
+    (478, "BA", "Bosnia and Herzegovina"),
+    (501, "TF", "Adélie Land"),
+    (503, "AU", "Australia"),
+    (506, "MM", "Myanmar"),
+    (508, "BN", "Brunei"),
+    (510, "FM", "Micronesia"),
+    (511, "PW", "Palau"),
+    (512, "NZ", "New Zealand"),
+    (514, "KH", "Cambodia"),
+    (515, "KH", "Cambodia"),
+    (516, "CX", "Christmas Island"),
+    (518, "CK", "Cook Islands"),
+    (520, "FJ", "Fiji"),
+    (523, "CC", "Cocos (Keeling) Islands"),
+    (525, "ID", "Indonesia"),
+    (529, "KI", "Kiribati"),
+    (531, "LA", "Laos"),
+    (533, "MY", "Malaysia"),
+    (536, "MP", "Northern Mariana Islands"),
+    (538, "MH", "Marshall Islands"),
+    (540, "NC", "New Caledonia"),
+    (542, "NU", "Niue"),
+    (544, "NR", "Nauru"),
+    (546, "PF", "French Polynesia"),
+    (548, "PH", "Philippines"),
+    (550, "TL", "Timor-Leste"),
+    (553, "PG", "Papua New Guinea"),
+    (555, "PN", "Pitcairn Islands"),
+    (557, "SB", "Solomon Islands"),
+    (559, "AS", "American Samoa"),
+    (561, "WS", "Samoa"),
+    (563, "SG", "Singapore"),
+    (564, "SG", "Singapore"),
+    (565, "SG", "Singapore"),
+    (566, "SG", "Singapore"),
+    (567, "TH", "Thailand"),
+    (570, "TO", "Tonga"),
+    (572, "TV", "Tuvalu"),
+    (574, "VN", "Vietnam"),
+    (576, "VU", "Vanuatu"),
+    (577, "VU", "Vanuatu"),
+    (578, "WF", "Wallis and Futuna"),
+    (601, "ZA", "South Africa"),
+    (603, "AO", "Angola"),
+    (605, "DZ", "Algeria"),
+    (607, "TF", "Saint Paul and Amsterdam Islands"),
+    (608, "SH", "Ascension Island"),
+    (609, "BI", "Burundi"),
+    (610, "BJ", "Benin"),
+    (611, "BW", "Botswana"),
+    (612, "CF", "Central African Republic"),
+    (613, "CM", "Cameroon"),
+    (615, "CG", "Congo"),
+    (616, "KM", "Comoros"),
+    (617, "CV", "Cabo Verde"),
+    (618, "TF", "Crozet Archipelago"),
+    (619, "CI", "Côte d'Ivoire"),
+    (620, "KM", "Comoros"),
+    (621, "DJ", "Djibouti"),
+    (622, "EG", "Egypt"),
+    (624, "ET", "Ethiopia"),
+    (625, "ER", "Eritrea"),
+    (626, "GA", "Gabon"),
+    (627, "GH", "Ghana"),
+    (629, "GM", "Gambia"),
+    (630, "GW", "Guinea-Bissau"),
+    (631, "GQ", "Equatorial Guinea"),
+    (632, "GN", "Guinea"),
+    (633, "BF", "Burkina Faso"),
+    (634, "KE", "Kenya"),
+    (635, "TF", "Kerguelen Islands"),
+    (636, "LR", "Liberia"),
+    (637, "LR", "Liberia"),
+    (638, "SS", "South Sudan"),
+    (642, "LY", "Libya"),
+    (644, "LS", "Lesotho"),
+    (645, "MU", "Mauritius"),
+    (647, "MG", "Madagascar"),
+    (649, "ML", "Mali"),
+    (650, "MZ", "Mozambique"),
+    (654, "MR", "Mauritania"),
+    (655, "MW", "Malawi"),
+    (656, "NE", "Niger"),
+    (657, "NG", "Nigeria"),
+    (659, "NA", "Namibia"),
+    (660, "RE", "Réunion"),
+    (661, "RW", "Rwanda"),
+    (662, "SD", "Sudan"),
+    (663, "SN", "Senegal"),
+    (664, "SC", "Seychelles"),
+    (665, "SH", "Saint Helena"),
+    (666, "SO", "Somalia"),
+    (667, "SL", "Sierra Leone"),
+    (668, "ST", "São Tomé and Príncipe"),
+    (669, "SZ", "Eswatini"),
+    (670, "TD", "Chad"),
+    (671, "TG", "Togo"),
+    (672, "TN", "Tunisia"),
+    (674, "TZ", "Tanzania"),
+    (675, "UG", "Uganda"),
+    (676, "CD", "Democratic Republic of the Congo"),
+    (677, "TZ", "Tanzania"),
+    (678, "ZM", "Zambia"),
+    (679, "ZW", "Zimbabwe"),
+    (701, "AR", "Argentina"),
+    (710, "BR", "Brazil"),
+    (720, "BO", "Bolivia"),
+    (725, "CL", "Chile"),
+    (730, "CO", "Colombia"),
+    (735, "EC", "Ecuador"),
+    (740, "FK", "Falkland Islands"),
+    (745, "GF", "French Guiana"),
+    (750, "GY", "Guyana"),
+    (755, "PY", "Paraguay"),
+    (760, "PE", "Peru"),
+    (765, "SR", "Suriname"),
+    (770, "UY", "Uruguay"),
+    (775, "VE", "Venezuela"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind() {
+        for (mmsi, kind, mid) in [
+            (227006760, MmsiKind::Ship, Some(227)),
+            (22751000, MmsiKind::Group, Some(227)),
+            (2275200, MmsiKind::CoastStation, Some(227)),
+            (111232511, MmsiKind::SarAircraft, Some(232)),
+            (823112345, MmsiKind::Handheld, Some(231)),
+            (970123456, MmsiKind::AisSart, None),
+            (972123456, MmsiKind::ManOverboard, None),
+            (974123456, MmsiKind::Epirb, None),
+            (982326789, MmsiKind::AuxiliaryCraft, Some(232)),
+            (992351001, MmsiKind::AidToNavigation, Some(235)),
+            (123456789, MmsiKind::Invalid, None),
+            (1_000_000_000, MmsiKind::Invalid, None),
+        ] {
+            let mmsi = Mmsi(mmsi);
+            assert_eq!(mmsi.kind(), kind, "{mmsi}");
+            assert_eq!(mmsi.mid(), mid, "{mmsi}");
+            assert_eq!(kind.name().parse::<MmsiKind>().unwrap(), kind);
+        }
+        assert!("boat".parse::<MmsiKind>().is_err());
+    }
+
+    #[test]
+    fn test_flag_state() {
+        let flag = Mmsi(366999712).flag_state().unwrap();
+        assert_eq!((flag.code, flag.name), ("US", "United States"));
+        assert_eq!(Mmsi(2275200).flag_state().unwrap().code, "FR");
+        assert_eq!(flag_state(775).unwrap().name, "Venezuela");
+        assert_eq!(flag_state(217), None);
+        assert_eq!(Mmsi(970123456).flag_state(), None);
+
+        assert!(Mmsi(227006760).is_valid());
+        assert!(Mmsi(970123456).is_valid());
+        // Right shape, but 217 isn't allocated
+        assert!(!Mmsi(217000001).is_valid());
+        assert!(!Mmsi(123456789).is_valid());
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("002275200".parse::<Mmsi>().unwrap(), Mmsi(2275200));
+        assert_eq!(Mmsi(2275200).to_string(), "002275200");
+        assert!("12345678a".parse::<Mmsi>().is_err());
+        assert!("1234567890".parse::<Mmsi>().is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_serialize() {
+        assert_eq!(
+            serde_json::to_string(&Mmsi(2275200)).unwrap(),
+            r#"{"number":2275200,"kind":"coast-station","flag_state":{"mid":227,"code":"FR","name":"France"}}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&Mmsi(970123456)).unwrap(),
+            r#"{"number":970123456,"kind":"ais-sart"}"#
+        );
+    }
+}
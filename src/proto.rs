@@ -1,18 +1,8 @@
 include!(concat!(env!("OUT_DIR"), "/proto_generated/mod.rs"));
 
-bit_struct::bit_struct! {
-    // u8 is the base storage type. This can be any multiple of 8
-    pub struct EncodedMetadata(u64) {
-        talker: crate::sentence::TalkerID,
-        length: u8,
-        index: u8,
-        message_id: u8,
-        channel: crate::sentence::ChannelCode,
-        drop_bits: bit_struct::u3,
-        garbage_bits: u8,
-        checksum: u8,
-    }
-}
+pub mod metadata;
+
+pub use metadata::MetadataFields;
 
 impl From<String> for spec::message::Types {
     fn from(s: String) -> Self {
@@ -24,6 +14,7 @@ impl<'a, 'b> From<&'a crate::sentence::Nmea<'b>> for spec::message::Types {
     fn from(sentence: &crate::sentence::Nmea) -> Self {
         let crate::sentence::Metadata {
             talker,
+            sentence_type,
             length,
             index,
             message_id,
@@ -31,9 +22,10 @@ impl<'a, 'b> From<&'a crate::sentence::Nmea<'b>> for spec::message::Types {
             fill_bits,
             checksum,
         } = sentence.metadata;
-        match crate::armor::unpack(&*sentence.body, fill_bits.value()) {
-            Ok((data, drop_bits, garbage_bits)) => {
-                let metadata = EncodedMetadata::new(
+        let encoded = crate::armor::unpack(&sentence.body, fill_bits.value()).and_then(
+            |(data, drop_bits, garbage_bits)| {
+                let metadata = MetadataFields {
+                    sentence_type,
                     talker,
                     length,
                     index,
@@ -42,12 +34,27 @@ impl<'a, 'b> From<&'a crate::sentence::Nmea<'b>> for spec::message::Types {
                     drop_bits,
                     garbage_bits,
                     checksum,
-                );
+                    bad_checksum: sentence.compute_checksum() != checksum,
+                    recompute_checksum: false,
+                };
                 let mut encoded = spec::Encoded::new();
-                encoded.set_metadata(metadata.raw());
+                // Packed metadata is always smaller when the sentence fits
+                if let Some(packed) = metadata.encode_packed() {
+                    encoded.set_packed_metadata(packed);
+                } else {
+                    let (metadata, escaped_talker) =
+                        metadata.encode().map_err(|_| "metadata overflow")?;
+                    encoded.set_metadata(metadata);
+                    if let Some(talker) = escaped_talker {
+                        encoded.set_talker(talker);
+                    }
+                }
                 encoded.set_body(data);
-                spec::message::Types::Encoded(encoded)
-            }
+                Ok(encoded)
+            },
+        );
+        match encoded {
+            Ok(encoded) => spec::message::Types::Encoded(encoded),
             Err(e) => {
                 eprintln!("Failed to unpack '{sentence}': {e}");
                 sentence.to_string().into()
@@ -56,16 +63,138 @@ impl<'a, 'b> From<&'a crate::sentence::Nmea<'b>> for spec::message::Types {
     }
 }
 
+// Layout byte for each field of a `Generic` sentence. Numbers carry the
+// number of decimal places in bits 3..6 and the number of extra leading zeros
+// in bits 0..3.
+const FIELD_EMPTY: u8 = 0x00;
+const FIELD_TEXT: u8 = 0x40;
+const FIELD_NUMBER: u8 = 0x80;
+
+// Keep well within i64
+const MAX_DIGITS: usize = 18;
+
+/// Splits a decimal field such as "-012.50" into its mantissa and layout
+/// byte, if it can be reproduced exactly.
+fn encode_number(field: &str) -> Option<(i64, u8)> {
+    let (negative, digits) = match field.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, field),
+    };
+    let (int_part, frac_part) = digits.split_once('.').unwrap_or((digits, ""));
+    if int_part.is_empty()
+        || (digits.contains('.') && frac_part.is_empty())
+        || !int_part
+            .bytes()
+            .chain(frac_part.bytes())
+            .all(|b| b.is_ascii_digit())
+        || int_part.len() + frac_part.len() > MAX_DIGITS
+        || frac_part.len() > 7
+    {
+        return None;
+    }
+    let magnitude: i64 = format!("{int_part}{frac_part}").parse().ok()?;
+    // "-0" can't be told apart from "0"
+    if negative && magnitude == 0 {
+        return None;
+    }
+    let natural = magnitude.to_string().len().max(frac_part.len() + 1);
+    let extra_zeros = int_part.len() + frac_part.len() - natural;
+    if extra_zeros > 7 {
+        return None;
+    }
+    let mantissa = if negative { -magnitude } else { magnitude };
+    Some((
+        mantissa,
+        FIELD_NUMBER | (frac_part.len() as u8) << 3 | extra_zeros as u8,
+    ))
+}
+
+fn decode_number(mantissa: i64, layout: u8) -> String {
+    let decimals = usize::from((layout >> 3) & 0x07);
+    let extra_zeros = usize::from(layout & 0x07);
+    let width = decimals + 1;
+    let digits = format!("{:0width$}", mantissa.unsigned_abs());
+    let digits = format!("{}{digits}", "0".repeat(extra_zeros));
+    let (int_part, frac_part) = digits.split_at(digits.len() - decimals);
+    let sign = if mantissa < 0 { "-" } else { "" };
+    if decimals > 0 {
+        format!("{sign}{int_part}.{frac_part}")
+    } else {
+        format!("{sign}{int_part}")
+    }
+}
+
+impl<'a, 'b> From<&'a crate::sentence::Generic<'b>> for spec::Generic {
+    fn from(sentence: &crate::sentence::Generic) -> Self {
+        let mut g = spec::Generic::new();
+        g.set_address(sentence.address.to_owned());
+        let mut layout = Vec::with_capacity(sentence.fields.len());
+        for field in &sentence.fields {
+            if field.is_empty() {
+                layout.push(FIELD_EMPTY);
+            } else if let Some((mantissa, l)) = encode_number(field) {
+                g.numbers.push(mantissa);
+                layout.push(l);
+            } else {
+                g.texts.push((*field).to_owned());
+                layout.push(FIELD_TEXT);
+            }
+        }
+        g.set_layout(layout);
+        if let Some(checksum) = sentence.checksum {
+            g.set_checksum(checksum.into());
+        }
+        g
+    }
+}
+
+impl spec::Generic {
+    pub fn try_write<W: std::io::Write>(&self, mut writer: W) -> anyhow::Result<()> {
+        let mut numbers = self.numbers.iter();
+        let mut texts = self.texts.iter();
+        write!(writer, "{}", self.address())?;
+        for &l in self.layout() {
+            write!(writer, ",")?;
+            match l & 0xc0 {
+                FIELD_EMPTY => {}
+                FIELD_TEXT => {
+                    let Some(text) = texts.next() else {
+                        anyhow::bail!("Missing text field");
+                    };
+                    write!(writer, "{text}")?;
+                }
+                FIELD_NUMBER => {
+                    let Some(&mantissa) = numbers.next() else {
+                        anyhow::bail!("Missing number field");
+                    };
+                    write!(writer, "{}", decode_number(mantissa, l))?;
+                }
+                _ => anyhow::bail!("Invalid field layout {l:#04x}"),
+            }
+        }
+        if self.has_checksum() {
+            write!(writer, "*{:02X}", self.checksum())?;
+        }
+        Ok(())
+    }
+}
+
 impl std::str::FromStr for spec::message::Types {
     type Err = std::convert::Infallible;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Ok(String::new().into());
+        }
         match crate::sentence::Nmea::parse(s) {
             Ok(sentence) => Ok((&sentence).into()),
-            Err(e) => {
-                eprintln!("Failed to unpack '{s}': {e}");
-                Ok(s.to_owned().into())
-            }
+            Err(e) => match crate::sentence::Generic::parse(s) {
+                Ok(sentence) => Ok(spec::message::Types::Generic((&sentence).into())),
+                Err(_) => {
+                    eprintln!("Failed to unpack '{s}': {e}");
+                    Ok(s.to_owned().into())
+                }
+            },
         }
     }
 }
@@ -77,6 +206,9 @@ impl From<spec::message::Types> for spec::Message {
             spec::message::Types::Raw(t) => m.set_raw(t),
             spec::message::Types::Encoded(e) => m.set_encoded(e),
             spec::message::Types::Repeat(p) => m.set_repeat(p),
+            spec::message::Types::Generic(g) => m.set_generic(g),
+            spec::message::Types::Sync(s) => m.set_sync(s),
+            spec::message::Types::Index(i) => m.set_index(i),
         }
         m
     }
@@ -85,8 +217,14 @@ impl From<spec::message::Types> for spec::Message {
 impl std::str::FromStr for spec::Message {
     type Err = std::convert::Infallible;
 
+    /// Parses the sentence after any tag block, which is kept alongside
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        s.parse::<spec::message::Types>().map(Into::into)
+        let (tag_block, s) = crate::sentence::split_tag_block(s);
+        let mut message: spec::Message = s.parse::<spec::message::Types>()?.into();
+        if !tag_block.is_empty() {
+            message.set_tag_block(tag_block.to_owned());
+        }
+        Ok(message)
     }
 }
 
@@ -99,46 +237,145 @@ impl From<String> for spec::Message {
 impl<'a> TryFrom<&'a spec::Encoded> for crate::sentence::Nmea<'a> {
     type Error = anyhow::Error;
     fn try_from(e: &'a spec::Encoded) -> Result<Self, Self::Error> {
-        let Ok(mut metadata) = EncodedMetadata::try_from(e.metadata()) else {
-            anyhow::bail!("Failed to parse metadata");
-        };
-        let Ok((packed, fill_bits)) = crate::armor::pack(
-            e.body(),
-            metadata.drop_bits().get(),
-            metadata.garbage_bits().get(),
-        ) else {
+        let metadata = e.metadata_fields()?;
+        let Ok((packed, fill_bits)) =
+            crate::armor::pack(e.body(), metadata.drop_bits, metadata.garbage_bits)
+        else {
             anyhow::bail!("Failed to read packing");
         };
 
-        Ok(crate::sentence::Nmea {
+        let mut nmea = crate::sentence::Nmea {
             metadata: crate::sentence::Metadata {
-                talker: metadata.talker().get(),
-                length: metadata.length().get(),
-                index: metadata.index().get(),
-                message_id: metadata.message_id().get(),
-                channel: metadata.channel().get(),
+                talker: metadata.talker,
+                sentence_type: metadata.sentence_type,
+                length: metadata.length,
+                index: metadata.index,
+                message_id: metadata.message_id,
+                channel: metadata.channel,
                 fill_bits,
-                checksum: metadata.checksum().get(),
+                checksum: metadata.checksum,
             },
             body: packed.into(),
-        })
+        };
+        if metadata.recompute_checksum {
+            nmea.metadata.checksum = nmea.compute_checksum();
+        }
+        Ok(nmea)
+    }
+}
+
+impl spec::LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            spec::LineEnding::LF => "\n",
+            spec::LineEnding::CRLF => "\r\n",
+            spec::LineEnding::CR => "\r",
+            spec::LineEnding::NONE => "",
+        }
+    }
+}
+
+/// Splits a line as returned by [`std::io::BufRead::read_line`] into its
+/// content, any trailing whitespace, and the line terminator.
+pub fn split_line(line: &str) -> (&str, &str, spec::LineEnding) {
+    let (rest, ending) = if let Some(rest) = line.strip_suffix("\r\n") {
+        (rest, spec::LineEnding::CRLF)
+    } else if let Some(rest) = line.strip_suffix('\n') {
+        (rest, spec::LineEnding::LF)
+    } else if let Some(rest) = line.strip_suffix('\r') {
+        (rest, spec::LineEnding::CR)
+    } else {
+        (line, spec::LineEnding::NONE)
+    };
+    let content = rest.trim_end();
+    (content, &rest[content.len()..], ending)
+}
+
+impl spec::Encoded {
+    /// Decodes whichever metadata representation was sent
+    pub fn metadata_fields(&self) -> anyhow::Result<MetadataFields> {
+        if self.has_packed_metadata() {
+            MetadataFields::decode_packed(self.packed_metadata())
+        } else {
+            MetadataFields::decode(self.metadata(), self.talker())
+        }
+    }
+
+    /// Drops the checksum from packed metadata, leaving the decoder to
+    /// recompute it. Returns whether it was dropped, which is only possible
+    /// for packed metadata with a valid checksum.
+    pub fn omit_checksum(&mut self) -> anyhow::Result<bool> {
+        if !self.has_packed_metadata() {
+            return Ok(false);
+        }
+        let mut metadata = self.metadata_fields()?;
+        metadata.recompute_checksum = true;
+        metadata.checksum = 0;
+        match metadata.encode_packed() {
+            Some(packed) => {
+                self.set_packed_metadata(packed);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    /// Whether the sentence was encoded with a checksum known not to match
+    pub fn checksum_known_bad(&self) -> bool {
+        self.metadata_fields()
+            .is_ok_and(|metadata| metadata.bad_checksum)
     }
 }
 
 impl spec::Message {
-    pub fn try_write<W: std::io::Write>(&self, mut writer: W) -> anyhow::Result<()> {
+    pub fn try_write<W: std::io::Write>(&self, writer: W) -> anyhow::Result<()> {
+        self.try_write_with(writer, false)
+    }
+
+    /// Like [`Self::try_write`], but with the option of recomputing the
+    /// checksum of encoded sentences instead of replaying the stored one.
+    /// Raw strings are always written verbatim.
+    pub fn try_write_with<W: std::io::Write>(
+        &self,
+        mut writer: W,
+        recompute_checksum: bool,
+    ) -> anyhow::Result<()> {
+        if !self.has_encoded() && !self.has_generic() && !self.has_raw() {
+            anyhow::bail!("Message has no sentence to write");
+        }
+        writer.write_all(self.tag_block().as_bytes())?;
         if self.has_encoded() {
             let e = self.encoded();
             let nmea = crate::sentence::Nmea::try_from(e)?;
-            write!(writer, "{}", nmea)?;
-        } else if self.has_raw() {
-            write!(writer, "{}", self.raw())?;
+            if recompute_checksum {
+                write!(writer, "{}", nmea.display_recomputed())?;
+            } else {
+                write!(writer, "{}", nmea)?;
+            }
+        } else if self.has_generic() {
+            self.generic().try_write(writer)?;
         } else {
-            panic!("Unexpected message type");
+            write!(writer, "{}", self.raw())?;
         }
         Ok(())
     }
 
+    /// Records the whitespace and terminator which followed the message, so
+    /// the line can be reproduced exactly. A bare "\n" costs nothing.
+    pub fn set_ending(&mut self, trailing_whitespace: &str, line_ending: spec::LineEnding) {
+        if line_ending != spec::LineEnding::LF {
+            self.set_line_ending(line_ending);
+        }
+        if !trailing_whitespace.is_empty() {
+            self.set_trailing_whitespace(trailing_whitespace.to_owned());
+        }
+    }
+
+    pub fn write_ending<W: std::io::Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writer.write_all(self.trailing_whitespace().as_bytes())?;
+        writer.write_all(self.line_ending().as_str().as_bytes())
+    }
+
     pub fn try_to_string(&self) -> anyhow::Result<String> {
         let mut s = Vec::new();
         self.try_write(&mut s)?;
@@ -150,6 +387,14 @@ impl spec::Message {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_write_without_sentence() {
+        assert!(spec::Message::new().try_to_string().is_err());
+        let mut sync = spec::Message::new();
+        sync.set_sync(spec::SyncPoint::new());
+        assert!(sync.try_to_string().is_err());
+    }
+
     #[test]
     fn test_parse_valid() {
         let s = "!AIVDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*23";
@@ -168,14 +413,229 @@ mod tests {
         match s.parse::<spec::Message>() {
             Ok(m) => {
                 assert!(!m.has_encoded());
-                assert!(m.has_raw());
-                assert!(m.raw() == s);
+                assert!(m.has_generic());
                 assert!(m.try_to_string().unwrap() == s);
             }
             Err(e) => match e {},
         }
     }
 
+    #[test]
+    fn test_tag_block() {
+        use protobuf::Message;
+
+        for (s, encoded) in [
+            (
+                "\\s:r003669945,c:1241544035*4A\\!AIVDM,1,1,,B,15N4cJ`005Jrek0H@9n`DW5608EP,0*13",
+                true,
+            ),
+            ("\\c:1241544035*4A\\$PSRT,,-0,1.,*00", false),
+            ("\\c:1241544035*4A\\", false),
+        ] {
+            let m = s.parse::<spec::Message>().unwrap_or_else(|e| match e {});
+            assert_eq!(m.has_encoded(), encoded, "{s}");
+            assert!(s.starts_with(m.tag_block()) && m.tag_block().ends_with('\\'));
+            let m = spec::Message::parse_from_bytes(&m.write_to_bytes().unwrap()).unwrap();
+            assert_eq!(m.try_to_string().unwrap(), s);
+        }
+        assert_eq!(
+            crate::verify_checksum(
+                "\\c:1241544035*4A\\!AIVDM,1,1,,B,15N4cJ`005Jrek0H@9n`DW5608EP,0*13"
+            )
+            .unwrap(),
+            (true, 0x13)
+        );
+
+        let m = "!AIVDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*23"
+            .parse::<spec::Message>()
+            .unwrap_or_else(|e| match e {});
+        assert!(!m.has_tag_block());
+    }
+
+    #[test]
+    fn test_bad_checksum() {
+        let s = "!AIVDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*24";
+        match s.parse::<spec::Message>() {
+            Ok(m) => {
+                assert!(m.has_encoded());
+                assert!(m.encoded().checksum_known_bad());
+                assert_eq!(m.try_to_string().unwrap(), s);
+            }
+            Err(e) => match e {},
+        }
+
+        let s = "!AIVDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*23";
+        match s.parse::<spec::Message>() {
+            Ok(m) => assert!(!m.encoded().checksum_known_bad()),
+            Err(e) => match e {},
+        }
+    }
+
+    #[test]
+    fn test_numbers() {
+        for s in [
+            "0",
+            "7",
+            "-3",
+            "4807.038",
+            "01131.000",
+            "022.4",
+            "0.5",
+            "-0.25",
+            "000",
+        ] {
+            let (mantissa, layout) = encode_number(s).unwrap_or_else(|| panic!("{s}"));
+            assert_eq!(decode_number(mantissa, layout), s);
+        }
+        for s in [
+            "-0",
+            "-0.0",
+            "1.",
+            ".5",
+            "1e5",
+            "A",
+            "0000000001",
+            "1.23456789",
+        ] {
+            assert!(encode_number(s).is_none(), "{s}");
+        }
+    }
+
+    #[test]
+    fn test_generic() {
+        for s in [
+            "$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A",
+            "$PSRT,,-0,1.,*00",
+            "$PSRT",
+        ] {
+            match s.parse::<spec::Message>() {
+                Ok(m) => {
+                    assert!(m.has_generic());
+                    assert_eq!(m.try_to_string().unwrap(), s);
+                }
+                Err(e) => match e {},
+            }
+        }
+    }
+
+    #[test]
+    fn test_other_talker() {
+        for (s, escaped) in [
+            ("!XYVDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*2A", false),
+            ("!Z9VDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*48", false),
+            ("!a7VDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*7D", true),
+        ] {
+            match s.parse::<spec::Message>() {
+                Ok(m) => {
+                    assert!(m.has_encoded());
+                    assert_eq!(m.encoded().has_talker(), escaped);
+                    assert_eq!(m.try_to_string().unwrap(), s);
+                }
+                Err(e) => match e {},
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_line() {
+        use spec::LineEnding;
+        let s = "!AIVDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*23";
+        for (suffix, trailing, ending) in [
+            ("\n", "", LineEnding::LF),
+            ("\r\n", "", LineEnding::CRLF),
+            (" \t\r\n", " \t", LineEnding::CRLF),
+            ("\r", "", LineEnding::CR),
+            ("\r \n", "\r ", LineEnding::LF),
+            ("", "", LineEnding::NONE),
+        ] {
+            let line = format!("{s}{suffix}");
+            assert_eq!(split_line(&line), (s, trailing, ending));
+
+            let mut m = s.parse::<spec::Message>().unwrap_or_else(|e| match e {});
+            m.set_ending(trailing, ending);
+            let mut out = m.try_to_string().unwrap().into_bytes();
+            m.write_ending(&mut out).unwrap();
+            assert_eq!(String::from_utf8(out).unwrap(), line);
+        }
+    }
+
+    #[test]
+    fn test_blank_lines() {
+        use protobuf::Message;
+
+        let input =
+            "!AIVDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*23\n\n \t\n\r\n  \r\n$GPGGA,1\r\n\n";
+        let mut output = Vec::new();
+        for line in input.split_inclusive('\n') {
+            let (content, trailing, ending) = split_line(line);
+            let mut m = content
+                .parse::<spec::Message>()
+                .unwrap_or_else(|e| match e {});
+            m.set_ending(trailing, ending);
+            let bytes = m.write_length_delimited_to_bytes().unwrap();
+            let m = protobuf::CodedInputStream::from_bytes(&bytes)
+                .read_message::<spec::Message>()
+                .unwrap();
+            if content.is_empty() {
+                assert!(m.has_raw());
+            }
+            m.try_write(&mut output).unwrap();
+            m.write_ending(&mut output).unwrap();
+        }
+        assert_eq!(String::from_utf8(output).unwrap(), input);
+    }
+
+    const CORPUS: &[&str] = &[
+        "!AIVDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*23",
+        "!AIVDM,2,1,3,A,55Upuv00?I98cQW?OC<th4P0000000000000000U40?,0*3B",
+        "!AIVDM,1,1,,B,177KQJ5000G?tO`K>RA1wUbN0TKH,0*5C",
+        "!AIVDM,2,1,1,B,53cjbg00?ImDTs;;;J0l4Tr22222222222222209000,0*51",
+        "!AIVDM,2,2,1,B,88888888880,2*26",
+        "!AIVDM,1,1,,A,802R5Ph0BkDhjPF?qRGbOwwwwwwwwwww2wwwwwwwwwwwwwwwwwwwwwwwwww,2*3B",
+        "!AIVDM,1,1,,B,B>eq`d@0;6Cb0>4tL<Ghowq5wP06,0*79",
+        "!AIVDM,1,1,,2,13HOI:0P0000VOHLCnHQKwvL05Ip,0*50",
+    ];
+
+    #[test]
+    fn test_packed_metadata_saving() {
+        use protobuf::Message;
+
+        let (mut packed_total, mut fixed_total) = (0, 0);
+        for s in CORPUS {
+            let m = s.parse::<spec::Message>().unwrap_or_else(|e| match e {});
+            assert_eq!(m.try_to_string().unwrap(), *s);
+            packed_total += m.compute_size();
+
+            // Same message, forced into the fixed64 layout
+            let mut fixed = m.clone();
+            let e = fixed.mut_encoded();
+            let (metadata, _) = e.metadata_fields().unwrap().encode().unwrap();
+            e.clear_packed_metadata();
+            e.set_metadata(metadata);
+            assert_eq!(fixed.try_to_string().unwrap(), *s);
+            fixed_total += fixed.compute_size();
+        }
+        // The channel 2 and garbage bits sentences need the fixed layout; the
+        // rest save 6 bytes (single) or 4 bytes (multipart) each
+        assert_eq!(fixed_total - packed_total, 3 * 6 + 3 * 4);
+    }
+
+    #[test]
+    fn test_omit_checksum() {
+        use protobuf::Message;
+
+        for s in CORPUS {
+            let mut m = s.parse::<spec::Message>().unwrap_or_else(|e| match e {});
+            let size = m.compute_size();
+            let packed = m.encoded().has_packed_metadata();
+            assert_eq!(m.mut_encoded().omit_checksum().unwrap(), packed);
+            assert_eq!(m.try_to_string().unwrap(), *s);
+            if packed {
+                assert!(m.compute_size() < size, "{s}");
+            }
+        }
+    }
+
     #[test]
     fn test_full_round_trip_valid() {
         use protobuf::Message;
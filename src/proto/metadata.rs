@@ -0,0 +1,460 @@
+//! Layouts for the `fixed64 metadata` field of `Encoded`.
+//!
+//! The top four bits select the layout. Version 0 predates the marker and
+//! starts with a `TalkerID`, which only ever takes the values 0-9, so any
+//! later version claims one of the unused values counting down from 0xF.
+//!
+//! Common `!AIVDM` sentences can instead use the varint `packed_metadata`
+//! field, where the low two bits select one of a few fixed shapes and only
+//! the fields which vary within that shape are sent.
+
+use bit_struct::{u3, u4, u5, u11};
+
+use crate::sentence::{ChannelCode, SentenceType, Talker, TalkerID};
+
+bit_struct::bit_struct! {
+    /// Original layout, only ever carrying VDM sentences
+    pub struct EncodedMetadataV0(u64) {
+        talker: TalkerID,
+        length: u8,
+        index: u8,
+        message_id: u8,
+        channel: ChannelCode,
+        drop_bits: u3,
+        garbage_bits: u8,
+        checksum: u8,
+        // Set when the checksum didn't match the sentence on the way in,
+        // so the decoder can reproduce the corrupted line as-is
+        bad_checksum: bool,
+        // Non-zero for talkers which aren't a TalkerID, in which case the
+        // talker field is ignored. 0x7ff means the talker is carried in
+        // `Encoded.talker`, anything else is `1 + alnum_code`.
+        other_talker: u11,
+    }
+
+    pub struct EncodedMetadataV1(u64) {
+        // Always `V1_MARKER`
+        version: u4,
+        sentence_type: SentenceType,
+        // 0-9 for a TalkerID, then `10 + alnum_code`, or 0x7ff when the
+        // talker is carried in `Encoded.talker`
+        talker: u11,
+        length: u8,
+        index: u8,
+        message_id: u8,
+        channel: ChannelCode,
+        drop_bits: u3,
+        // Garbage is at most the 5 fill bits
+        garbage_bits: u5,
+        checksum: u8,
+        bad_checksum: bool,
+        // Must be zero, for use by future versions
+        reserved: u4,
+    }
+}
+
+pub const V1_MARKER: u8 = 0xf;
+
+/// Layout written by [`MetadataFields::encode`]
+pub const CURRENT_VERSION: u8 = 1;
+
+const TALKER_ESCAPE: u16 = 0x7ff;
+
+const TALKER_IDS: [TalkerID; 10] = [
+    TalkerID::AB,
+    TalkerID::AD,
+    TalkerID::AI,
+    TalkerID::AN,
+    TalkerID::AR,
+    TalkerID::AS,
+    TalkerID::AT,
+    TalkerID::AX,
+    TalkerID::BS,
+    TalkerID::SA,
+];
+
+// Flags for `packed_metadata`, in the low two bits.
+//
+// `!AIVDM,1,1,,{A|B},...`:
+//   channel B (1) | drop_bits (3) | checksum (8)
+//
+// `!AIVDM,{1-8},{1-8},{0-9 or empty},{A|B},...`, with `PACKED_MULTI`:
+//   length - 1 (3) | index - 1 (3) | message_id, 15 if empty (4)
+//   | channel B (1) | drop_bits (3) | checksum (8)
+//
+// With `PACKED_NO_CHECKSUM` the checksum is left off the end, and the
+// decoder recomputes it.
+const PACKED_MULTI: u64 = 0b01;
+const PACKED_NO_CHECKSUM: u64 = 0b10;
+
+/// Returns which layout `raw` is in
+pub fn version(raw: u64) -> anyhow::Result<u8> {
+    match (raw >> 60) as u8 {
+        0..=9 => Ok(0),
+        V1_MARKER => Ok(1),
+        x => anyhow::bail!("Unknown metadata layout {x:#x}"),
+    }
+}
+
+/// Every field carried by the metadata, independent of layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MetadataFields {
+    pub sentence_type: SentenceType,
+    pub talker: Talker,
+    pub length: u8,
+    pub index: u8,
+    pub message_id: u8,
+    pub channel: ChannelCode,
+    #[cfg_attr(feature = "serde", serde(serialize_with = "crate::bits::serialize_u3"))]
+    pub drop_bits: u3,
+    pub garbage_bits: u8,
+    pub checksum: u8,
+    pub bad_checksum: bool,
+    /// The checksum wasn't sent and should be recomputed from the sentence.
+    /// Only supported by the packed representation.
+    pub recompute_checksum: bool,
+}
+
+impl MetadataFields {
+    /// Reads metadata in any known layout. `escaped_talker` is the contents
+    /// of `Encoded.talker`, used when the talker didn't fit.
+    pub fn decode(raw: u64, escaped_talker: &str) -> anyhow::Result<Self> {
+        match version(raw)? {
+            0 => Self::decode_v0(raw, escaped_talker),
+            _ => Self::decode_v1(raw, escaped_talker),
+        }
+    }
+
+    fn decode_v0(raw: u64, escaped_talker: &str) -> anyhow::Result<Self> {
+        let Ok(mut m) = EncodedMetadataV0::try_from(raw) else {
+            anyhow::bail!("Failed to parse metadata");
+        };
+        let talker = match m.other_talker().get().value() {
+            0 => Talker::Known(m.talker().get()),
+            TALKER_ESCAPE => decode_escaped_talker(escaped_talker)?,
+            x => decode_alnum_talker(x - 1)?,
+        };
+        Ok(MetadataFields {
+            sentence_type: SentenceType::VDM,
+            talker,
+            length: m.length().get(),
+            index: m.index().get(),
+            message_id: m.message_id().get(),
+            channel: m.channel().get(),
+            drop_bits: m.drop_bits().get(),
+            garbage_bits: m.garbage_bits().get(),
+            checksum: m.checksum().get(),
+            bad_checksum: m.bad_checksum().get(),
+            recompute_checksum: false,
+        })
+    }
+
+    fn decode_v1(raw: u64, escaped_talker: &str) -> anyhow::Result<Self> {
+        let Ok(mut m) = EncodedMetadataV1::try_from(raw) else {
+            anyhow::bail!("Failed to parse metadata");
+        };
+        if m.reserved().get().value() != 0 {
+            anyhow::bail!("Reserved metadata bits set: {raw:#018x}");
+        }
+        let talker = match m.talker().get().value() {
+            x @ 0..=9 => Talker::Known(TALKER_IDS[usize::from(x)]),
+            TALKER_ESCAPE => decode_escaped_talker(escaped_talker)?,
+            x => decode_alnum_talker(x - 10)?,
+        };
+        Ok(MetadataFields {
+            sentence_type: m.sentence_type().get(),
+            talker,
+            length: m.length().get(),
+            index: m.index().get(),
+            message_id: m.message_id().get(),
+            channel: m.channel().get(),
+            drop_bits: m.drop_bits().get(),
+            garbage_bits: m.garbage_bits().get().value(),
+            checksum: m.checksum().get(),
+            bad_checksum: m.bad_checksum().get(),
+            recompute_checksum: false,
+        })
+    }
+
+    /// Encodes into the current layout, along with the talker string to put
+    /// in `Encoded.talker` if it didn't fit.
+    pub fn encode(&self) -> anyhow::Result<(u64, Option<String>)> {
+        let (talker, escaped) = match self.talker {
+            Talker::Known(id) => (u16::from(u8::from(id)), None),
+            Talker::Other(chars) => match alnum_code(chars) {
+                Some(code) => (10 + code, None),
+                None => (TALKER_ESCAPE, Some(self.talker.to_string())),
+            },
+        };
+        let Some(garbage_bits) = u5::new(self.garbage_bits) else {
+            anyhow::bail!("Too many garbage bits: {}", self.garbage_bits);
+        };
+        let metadata = EncodedMetadataV1::new(
+            u4::new(V1_MARKER).unwrap(),
+            self.sentence_type,
+            u11::new(talker).expect("u11 overflow"),
+            self.length,
+            self.index,
+            self.message_id,
+            self.channel,
+            self.drop_bits,
+            garbage_bits,
+            self.checksum,
+            self.bad_checksum,
+            u4::new(0).unwrap(),
+        );
+        Ok((metadata.raw(), escaped))
+    }
+
+    /// Packs the metadata into a small varint if the sentence has one of the
+    /// common shapes: an `!AIVDM` from an `AI` talker on channel A or B, with
+    /// no garbage bits and a valid checksum.
+    pub fn encode_packed(&self) -> Option<u64> {
+        if self.sentence_type != SentenceType::VDM
+            || self.talker != Talker::Known(TalkerID::AI)
+            || self.garbage_bits != 0
+            || self.bad_checksum
+        {
+            return None;
+        }
+        let channel_b = match self.channel {
+            ChannelCode::A => 0,
+            ChannelCode::B => 1,
+            _ => return None,
+        };
+        // Common to both shapes
+        let (flags, tail) = if self.recompute_checksum {
+            (
+                PACKED_NO_CHECKSUM,
+                channel_b | u64::from(self.drop_bits.value()) << 1,
+            )
+        } else {
+            (
+                0,
+                channel_b | u64::from(self.drop_bits.value()) << 1 | u64::from(self.checksum) << 4,
+            )
+        };
+        if self.length == 1 && self.index == 1 && self.message_id == 0xff {
+            return Some(flags | tail << 2);
+        }
+        if !(1..=8).contains(&self.length) || !(1..=self.length).contains(&self.index) {
+            return None;
+        }
+        let message_id = match self.message_id {
+            0xff => 15,
+            x @ 0..=9 => x,
+            _ => return None,
+        };
+        Some(
+            flags
+                | PACKED_MULTI
+                | u64::from(self.length - 1) << 2
+                | u64::from(self.index - 1) << 5
+                | u64::from(message_id) << 8
+                | tail << 12,
+        )
+    }
+
+    pub fn decode_packed(raw: u64) -> anyhow::Result<Self> {
+        let (length, index, message_id, tail) = if raw & PACKED_MULTI == 0 {
+            (1, 1, 0xff, raw >> 2)
+        } else {
+            let length = ((raw >> 2) & 0x7) as u8 + 1;
+            let index = ((raw >> 5) & 0x7) as u8 + 1;
+            let message_id = match ((raw >> 8) & 0xf) as u8 {
+                15 => 0xff,
+                x @ 0..=9 => x,
+                x => anyhow::bail!("Invalid packed message_id {x}"),
+            };
+            (length, index, message_id, raw >> 12)
+        };
+        let recompute_checksum = raw & PACKED_NO_CHECKSUM != 0;
+        let tail_bits = if recompute_checksum { 4 } else { 12 };
+        if tail >> tail_bits != 0 {
+            anyhow::bail!("Packed metadata too long: {raw:#x}");
+        }
+        Ok(MetadataFields {
+            sentence_type: SentenceType::VDM,
+            talker: Talker::Known(TalkerID::AI),
+            length,
+            index,
+            message_id,
+            channel: if tail & 1 == 0 {
+                ChannelCode::A
+            } else {
+                ChannelCode::B
+            },
+            drop_bits: u3::new(((tail >> 1) & 0x7) as u8).unwrap(),
+            garbage_bits: 0,
+            checksum: (tail >> 4) as u8,
+            bad_checksum: false,
+            recompute_checksum,
+        })
+    }
+}
+
+/// Two uppercase letters or digits, 36 * 36 combinations
+fn alnum_code([a, b]: [u8; 2]) -> Option<u16> {
+    fn index(c: u8) -> Option<u16> {
+        match c {
+            b'0'..=b'9' => Some((c - b'0').into()),
+            b'A'..=b'Z' => Some((c - b'A' + 10).into()),
+            _ => None,
+        }
+    }
+    Some(index(a)? * 36 + index(b)?)
+}
+
+fn decode_alnum_talker(code: u16) -> anyhow::Result<Talker> {
+    fn char(x: u16) -> u8 {
+        match x {
+            0..=9 => b'0' + x as u8,
+            _ => b'A' + (x - 10) as u8,
+        }
+    }
+    if code >= 36 * 36 {
+        anyhow::bail!("Invalid talker code {code}");
+    }
+    Ok(Talker::Other([char(code / 36), char(code % 36)]))
+}
+
+fn decode_escaped_talker(s: &str) -> anyhow::Result<Talker> {
+    match s.as_bytes() {
+        &[a, b] => Ok(Talker::Other([a, b])),
+        _ => anyhow::bail!("Invalid talker '{s}'"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields() -> MetadataFields {
+        // !AIVDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*23
+        MetadataFields {
+            sentence_type: SentenceType::VDM,
+            talker: Talker::Known(TalkerID::AI),
+            length: 1,
+            index: 1,
+            message_id: 0xff,
+            channel: ChannelCode::A,
+            drop_bits: u3::new(0).unwrap(),
+            garbage_bits: 0,
+            checksum: 0x23,
+            bad_checksum: false,
+            recompute_checksum: false,
+        }
+    }
+
+    #[test]
+    fn test_v0_layout() {
+        let raw = 0x2010_1ff2_0008_c000;
+        assert_eq!(version(raw).unwrap(), 0);
+        assert_eq!(MetadataFields::decode(raw, "").unwrap(), fields());
+
+        // Later additions to v0: bad checksum flag and other talkers
+        let raw = 0x2010_1ff2_0008_e000 | ((1 + 33 * 36 + 34) << 2);
+        let decoded = MetadataFields::decode(raw, "").unwrap();
+        assert!(decoded.bad_checksum);
+        assert_eq!(decoded.talker, Talker::Other(*b"XY"));
+    }
+
+    #[test]
+    fn test_v1_layout() {
+        let raw = 0xf002_0101_ff20_0460;
+        assert_eq!(version(raw).unwrap(), 1);
+        assert_eq!(fields().encode().unwrap(), (raw, None));
+        assert_eq!(MetadataFields::decode(raw, "").unwrap(), fields());
+
+        let m = MetadataFields {
+            sentence_type: SentenceType::VDO,
+            talker: Talker::Other(*b"XY"),
+            bad_checksum: true,
+            garbage_bits: 0x1f,
+            ..fields()
+        };
+        let (raw, escaped) = m.encode().unwrap();
+        assert_eq!(raw, 0xfcd0_0101_ff23_e470);
+        assert_eq!(escaped, None);
+        assert_eq!(MetadataFields::decode(raw, "").unwrap(), m);
+
+        let m = MetadataFields {
+            talker: Talker::Other(*b"a7"),
+            ..fields()
+        };
+        let (raw, escaped) = m.encode().unwrap();
+        assert_eq!(escaped.as_deref(), Some("a7"));
+        assert_eq!(MetadataFields::decode(raw, "a7").unwrap(), m);
+        assert!(MetadataFields::decode(raw, "").is_err());
+    }
+
+    #[test]
+    fn test_packed_layout() {
+        let raw = fields().encode_packed().unwrap();
+        assert_eq!(raw, 0x23 << 6);
+        assert_eq!(MetadataFields::decode_packed(raw).unwrap(), fields());
+
+        // !AIVDM,2,1,3,B,...,0 with 2 drop bits
+        let m = MetadataFields {
+            length: 2,
+            index: 1,
+            message_id: 3,
+            channel: ChannelCode::B,
+            drop_bits: u3::new(2).unwrap(),
+            ..fields()
+        };
+        let raw = m.encode_packed().unwrap();
+        assert_eq!(
+            raw,
+            0b1 | 0b001 << 2 | 3 << 8 | (0b1 | 0b010 << 1 | 0x23 << 4) << 12
+        );
+        assert_eq!(MetadataFields::decode_packed(raw).unwrap(), m);
+
+        // Checksum left off for the decoder to recompute
+        let m = MetadataFields {
+            checksum: 0,
+            recompute_checksum: true,
+            ..m
+        };
+        let raw = m.encode_packed().unwrap();
+        assert_eq!(raw, 0b11 | 0b001 << 2 | 3 << 8 | (0b1 | 0b010 << 1) << 12);
+        assert_eq!(MetadataFields::decode_packed(raw).unwrap(), m);
+        let m = MetadataFields {
+            checksum: 0,
+            recompute_checksum: true,
+            ..fields()
+        };
+        assert_eq!(m.encode_packed(), Some(0b10));
+
+        for m in [
+            MetadataFields {
+                channel: ChannelCode::Missing,
+                ..fields()
+            },
+            MetadataFields {
+                talker: Talker::Known(TalkerID::AB),
+                ..fields()
+            },
+            MetadataFields {
+                bad_checksum: true,
+                ..fields()
+            },
+            MetadataFields {
+                length: 9,
+                index: 1,
+                ..fields()
+            },
+        ] {
+            assert_eq!(m.encode_packed(), None, "{m:?}");
+        }
+    }
+
+    #[test]
+    fn test_invalid_layouts() {
+        assert!(version(0xa000_0000_0000_0000).is_err());
+        assert!(MetadataFields::decode(0xf002_0101_ff20_0461, "").is_err());
+        assert!(MetadataFields::decode_packed(0x1 << 20).is_err());
+        assert!(MetadataFields::decode_packed(0x2 | 0x1 << 6).is_err());
+    }
+}
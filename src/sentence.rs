@@ -7,6 +7,8 @@ enums! {
     pub TalkerID { AB, AD, AI, AN, AR, AS, AT, AX, BS, SA }
 
     pub ChannelCode { Missing, A, B, C1, C2 }
+
+    pub SentenceType { VDM, VDO }
 }
 
 impl fmt::Display for TalkerID {
@@ -26,6 +28,46 @@ impl fmt::Display for TalkerID {
     }
 }
 
+impl fmt::Display for SentenceType {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SentenceType::VDM => write!(fmt, "VDM"),
+            SentenceType::VDO => write!(fmt, "VDO"),
+        }
+    }
+}
+
+/// A two-character talker identifier. The common AIS talkers are kept as a
+/// [`TalkerID`] so they can be encoded compactly; anything else is carried
+/// as its raw characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Talker {
+    Known(TalkerID),
+    Other([u8; 2]),
+}
+
+impl Talker {
+    /// Characters which can appear in a talker without confusing the parser
+    pub fn is_valid_char(c: char) -> bool {
+        c.is_ascii_graphic() && !matches!(c, ',' | '*' | '!' | '$' | '\\')
+    }
+}
+
+impl From<TalkerID> for Talker {
+    fn from(id: TalkerID) -> Self {
+        Talker::Known(id)
+    }
+}
+
+impl fmt::Display for Talker {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Talker::Known(id) => write!(fmt, "{id}"),
+            Talker::Other([a, b]) => write!(fmt, "{}{}", char::from(*a), char::from(*b)),
+        }
+    }
+}
+
 impl fmt::Display for ChannelCode {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -38,9 +80,26 @@ impl fmt::Display for ChannelCode {
     }
 }
 
+/// Talkers, channels and sentence types serialize as they appear in the
+/// sentence.
+#[cfg(feature = "serde")]
+macro_rules! serialize_display {
+    ($($t:ty),*) => {$(
+        impl serde::Serialize for $t {
+            fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                s.collect_str(self)
+            }
+        }
+    )*};
+}
+
+#[cfg(feature = "serde")]
+serialize_display!(Talker, ChannelCode, SentenceType);
+
 #[derive(Debug)]
 pub struct Metadata {
-    pub talker: TalkerID,
+    pub talker: Talker,
+    pub sentence_type: SentenceType,
     pub length: u8,
     pub index: u8,
     pub message_id: u8,
@@ -57,10 +116,10 @@ pub struct Nmea<'a> {
 }
 
 impl<'a> Nmea<'a> {
-    pub fn parse(mut s: &'a str) -> anyhow::Result<Self> {
+    pub fn parse(s: &'a str) -> anyhow::Result<Self> {
         use winnow::Parser;
         Self::parse_inner
-            .parse(&mut s)
+            .parse(s)
             .map_err(|e| anyhow::format_err!("\n{e}"))
     }
 
@@ -74,21 +133,35 @@ impl<'a> Nmea<'a> {
         };
         '!'.parse_next(s)?;
         let talker = dispatch!(take(2usize);
-            "AB" => empty.value(TalkerID::AB),
-            "AD" => empty.value(TalkerID::AD),
-            "AI" => empty.value(TalkerID::AI),
-            "AN" => empty.value(TalkerID::AN),
-            "AR" => empty.value(TalkerID::AR),
-            "AS" => empty.value(TalkerID::AS),
-            "AT" => empty.value(TalkerID::AT),
-            "AX" => empty.value(TalkerID::AX),
-            "BS" => empty.value(TalkerID::BS),
-            "SA" => empty.value(TalkerID::SA),
-            _ => fail::<_, TalkerID, _>,
+            "AB" => empty.value(TalkerID::AB.into()),
+            "AD" => empty.value(TalkerID::AD.into()),
+            "AI" => empty.value(TalkerID::AI.into()),
+            "AN" => empty.value(TalkerID::AN.into()),
+            "AR" => empty.value(TalkerID::AR.into()),
+            "AS" => empty.value(TalkerID::AS.into()),
+            "AT" => empty.value(TalkerID::AT.into()),
+            "AX" => empty.value(TalkerID::AX.into()),
+            "BS" => empty.value(TalkerID::BS.into()),
+            "SA" => empty.value(TalkerID::SA.into()),
+            other => empty.verify_map(|_| {
+                let other: &str = other;
+                other.chars().all(Talker::is_valid_char).then(|| {
+                    let [a, b] = other.as_bytes() else { unreachable!() };
+                    Talker::Other([*a, *b])
+                })
+            }),
         )
         .context(StrContext::Label("talker_id"))
         .parse_next(s)?;
-        "VDM,".parse_next(s)?;
+        let sentence_type = terminated(
+            alt((
+                "VDM".value(SentenceType::VDM),
+                "VDO".value(SentenceType::VDO),
+            )),
+            ',',
+        )
+        .context(StrContext::Label("sentence_type"))
+        .parse_next(s)?;
         let length: u8 = terminated(digit1, ',')
             .parse_to()
             .context(StrContext::Label("length"))
@@ -126,6 +199,7 @@ impl<'a> Nmea<'a> {
             .parse_next(s)?;
         let metadata = Metadata {
             talker,
+            sentence_type,
             length,
             index,
             message_id: message_id.unwrap_or(0xff),
@@ -140,11 +214,30 @@ impl<'a> Nmea<'a> {
     }
 }
 
-impl<'a> fmt::Display for Nmea<'a> {
+impl<'a> Nmea<'a> {
+    /// Computes the checksum matching the current contents of the sentence,
+    /// which may differ from the stored `metadata.checksum`.
+    pub fn compute_checksum(&self) -> u8 {
+        Main(self).to_string().bytes().fold(0, |acc, b| acc ^ b)
+    }
+
+    /// Displays the sentence with a freshly computed checksum rather than
+    /// replaying the stored one.
+    pub fn display_recomputed(&self) -> impl fmt::Display + '_ {
+        Recomputed(self)
+    }
+}
+
+/// Everything between the leading '!' and the '*', i.e. the part covered by
+/// the checksum
+struct Main<'a, 'b>(&'a Nmea<'b>);
+
+impl<'a, 'b> fmt::Display for Main<'a, 'b> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        // !AIVDM,1,1,,B,177KQJ5000G?tO`K>RA1wUbN0TKH,0*5C
-        let Nmea { metadata: m, body } = self;
+        // AIVDM,1,1,,B,177KQJ5000G?tO`K>RA1wUbN0TKH,0
+        let Nmea { metadata: m, body } = self.0;
         let talker = m.talker;
+        let sentence_type = m.sentence_type;
         let length = m.length;
         let index = m.index;
         let message_id = m.message_id;
@@ -155,14 +248,126 @@ impl<'a> fmt::Display for Nmea<'a> {
         };
         let channel = m.channel;
         let fill_bits = m.fill_bits;
-        let checksum = m.checksum;
         write!(
             fmt,
-            "!{talker}VDM,{length},{index},{message_id},{channel},{body},{fill_bits}*{checksum:02X}"
+            "{talker}{sentence_type},{length},{index},{message_id},{channel},{body},{fill_bits}"
         )
     }
 }
 
+struct Recomputed<'a, 'b>(&'a Nmea<'b>);
+
+impl<'a, 'b> fmt::Display for Recomputed<'a, 'b> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let checksum = self.0.compute_checksum();
+        write!(fmt, "!{}*{checksum:02X}", Main(self.0))
+    }
+}
+
+impl<'a> fmt::Display for Nmea<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        // !AIVDM,1,1,,B,177KQJ5000G?tO`K>RA1wUbN0TKH,0*5C
+        let checksum = self.metadata.checksum;
+        write!(fmt, "!{}*{checksum:02X}", Main(self))
+    }
+}
+
+/// Any other NMEA 0183 sentence, split into its address field, data fields
+/// and optional checksum. Used for sentences [`Nmea`] doesn't understand.
+#[derive(Debug)]
+pub struct Generic<'a> {
+    /// Start delimiter and address, e.g. "$GPRMC"
+    pub address: &'a str,
+    pub fields: Vec<&'a str>,
+    pub checksum: Option<u8>,
+}
+
+impl<'a> Generic<'a> {
+    pub fn parse(s: &'a str) -> anyhow::Result<Self> {
+        use winnow::Parser;
+        Self::parse_inner
+            .parse(s)
+            .map_err(|e| anyhow::format_err!("\n{e}"))
+    }
+
+    fn parse_inner(s: &mut &'a str) -> winnow::Result<Self> {
+        use winnow::{
+            Parser,
+            combinator::{alt, opt, preceded, separated},
+            error::StrContext,
+            token::{one_of, take, take_while},
+        };
+        let address = (
+            one_of(('!', '$')),
+            take_while(1.., |c: char| c.is_ascii_alphanumeric()),
+        )
+            .take()
+            .context(StrContext::Label("address"))
+            .parse_next(s)?;
+        let field = take_while(0.., |c: char| c.is_ascii() && !"*,!$\r\n".contains(c));
+        let fields: Vec<&str> = alt((
+            preceded(',', separated(1.., field, ',')),
+            winnow::combinator::empty.value(Vec::new()),
+        ))
+        .context(StrContext::Label("fields"))
+        .parse_next(s)?;
+        let checksum = opt(preceded(
+            '*',
+            take(2usize).try_map(|s| u8::from_str_radix(s, 16)),
+        ))
+        .context(StrContext::Label("checksum"))
+        .parse_next(s)?;
+        Ok(Generic {
+            address,
+            fields,
+            checksum,
+        })
+    }
+}
+
+impl<'a> fmt::Display for Generic<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}", self.address)?;
+        for field in &self.fields {
+            write!(fmt, ",{field}")?;
+        }
+        if let Some(checksum) = self.checksum {
+            write!(fmt, "*{checksum:02X}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits an NMEA 4.0 tag block, such as `\s:r003669945,c:1241544035*4A\`,
+/// from the start of a line. The tag block keeps its backslashes, and is
+/// empty if the line doesn't start with one.
+pub fn split_tag_block(line: &str) -> (&str, &str) {
+    match line.strip_prefix('\\').and_then(|rest| rest.find('\\')) {
+        Some(end) => line.split_at(end + 2),
+        None => ("", line),
+    }
+}
+
+/// Reads the `c:` receive time from an NMEA 4.0 tag block at the start of
+/// a line, such as `\s:r003669945,c:1241544035*4A\!AIVDM,...`, in
+/// milliseconds since the Unix epoch. The tag block checksum isn't checked.
+pub fn tag_block_time(line: &str) -> Option<i64> {
+    let (block, _) = split_tag_block(line);
+    let block = block.strip_prefix('\\')?.strip_suffix('\\')?;
+    let block = block.rsplit_once('*').map_or(block, |(fields, _)| fields);
+    let time = block
+        .split(',')
+        .find_map(|field| field.strip_prefix("c:"))?
+        .parse::<i64>()
+        .ok()?;
+    // Some receivers write milliseconds rather than seconds
+    Some(if time > 99_999_999_999 {
+        time
+    } else {
+        time * 1000
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -171,4 +376,98 @@ mod tests {
     fn parse_1() {
         Nmea::parse("!AIVDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*23").unwrap();
     }
+
+    #[test]
+    fn parse_vdo() {
+        let s = "!AIVDO,1,1,,,B>eq`d@0;6Cb0>4tL<Ghowq5wP06,0*39";
+        let sentence = Nmea::parse(s).unwrap();
+        assert_eq!(sentence.metadata.sentence_type, SentenceType::VDO);
+        assert_eq!(sentence.to_string(), s);
+    }
+
+    #[test]
+    fn parse_other_talker() {
+        let s = "!BSVDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*3A";
+        let sentence = Nmea::parse(s).unwrap();
+        assert_eq!(sentence.metadata.talker, Talker::Known(TalkerID::BS));
+
+        let s = "!XYVDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*2A";
+        let sentence = Nmea::parse(s).unwrap();
+        assert_eq!(sentence.metadata.talker, Talker::Other(*b"XY"));
+        assert_eq!(sentence.to_string(), s);
+
+        let s = "!a7VDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*7D";
+        assert_eq!(Nmea::parse(s).unwrap().to_string(), s);
+
+        assert!(Nmea::parse("!A,VDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*26").is_err());
+    }
+
+    #[test]
+    fn parse_generic() {
+        for s in [
+            "$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A",
+            "$PSRT,,,*00",
+            "$PSRT",
+            "!AIVDO,1,1,,,B>eq`d@0;6Cb0>4tL<Ghowq5wP06,0*39",
+        ] {
+            let generic = Generic::parse(s).unwrap();
+            assert_eq!(generic.to_string(), s);
+        }
+        let generic = Generic::parse("$PSRT,,,*00").unwrap();
+        assert_eq!(generic.address, "$PSRT");
+        assert_eq!(generic.fields, ["", "", ""]);
+        assert_eq!(generic.checksum, Some(0));
+
+        assert!(Generic::parse("# comment").is_err());
+        assert!(Generic::parse("$GPGGA,1*2*3").is_err());
+    }
+
+    #[test]
+    fn recompute_checksum() {
+        let s = "!AIVDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*23";
+        let mut sentence = Nmea::parse(s).unwrap();
+        assert_eq!(sentence.compute_checksum(), 0x23);
+        assert_eq!(sentence.display_recomputed().to_string(), s);
+
+        sentence.metadata.channel = ChannelCode::B;
+        assert_eq!(
+            sentence.to_string(),
+            "!AIVDM,1,1,,B,13HOI:0P0000VOHLCnHQKwvL05Ip,0*23"
+        );
+        assert_eq!(
+            sentence.display_recomputed().to_string(),
+            "!AIVDM,1,1,,B,13HOI:0P0000VOHLCnHQKwvL05Ip,0*20"
+        );
+    }
+
+    #[test]
+    fn parse_tag_block() {
+        let line =
+            "\\s:r003669945,c:1241544035*4A\\!AIVDM,1,1,,B,15N4cJ`005Jrek0H@9n`DW5608EP,0*13";
+        assert_eq!(
+            split_tag_block(line),
+            (
+                "\\s:r003669945,c:1241544035*4A\\",
+                "!AIVDM,1,1,,B,15N4cJ`005Jrek0H@9n`DW5608EP,0*13"
+            )
+        );
+        assert_eq!(split_tag_block("\\c:1"), ("", "\\c:1"));
+        assert_eq!(split_tag_block("!AIVDM"), ("", "!AIVDM"));
+    }
+
+    #[test]
+    fn parse_tag_block_time() {
+        let line =
+            "\\s:r003669945,c:1241544035*4A\\!AIVDM,1,1,,B,15N4cJ`005Jrek0H@9n`DW5608EP,0*13";
+        assert_eq!(tag_block_time(line), Some(1_241_544_035_000));
+        assert_eq!(
+            tag_block_time("\\c:1241544035123\\!AIVDM"),
+            Some(1_241_544_035_123)
+        );
+        assert_eq!(tag_block_time("\\s:r003669945*4A\\!AIVDM"), None);
+        assert_eq!(
+            tag_block_time("!AIVDM,1,1,,B,15N4cJ`005Jrek0H@9n`DW5608EP,0*13"),
+            None
+        );
+    }
 }
@@ -0,0 +1,120 @@
+//! The window of recent messages which `Repeat`s refer back to, as kept by
+//! the decoding side.
+
+use crate::proto::spec;
+
+/// Messages are numbered in the order they were pushed, so callers can tell
+/// which one a repeat refers to.
+#[derive(Debug, Clone)]
+pub struct Window {
+    messages: Vec<Option<(u64, spec::Message)>>,
+    pos: usize,
+    count: u64,
+}
+
+impl Window {
+    pub fn new(size: usize) -> Self {
+        Window {
+            messages: vec![None; size],
+            pos: 0,
+            count: 0,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.messages.len()
+    }
+
+    /// Forgets every message, as at a sync point. Numbering carries on.
+    pub fn reset(&mut self) {
+        self.messages.fill(None);
+        self.pos = 0;
+    }
+
+    /// Looks up the message a repeat refers to, and returns its number along
+    /// with a copy carrying the repeat's own line ending.
+    pub fn resolve(&self, repeat: &spec::Message) -> anyhow::Result<(u64, spec::Message)> {
+        let window_size = self.messages.len();
+        let prev = usize::try_from(repeat.repeat().index())?;
+        if prev == 0 || prev >= window_size {
+            anyhow::bail!("Repeat index {prev} outside window of {window_size}");
+        }
+        let ix = if prev > self.pos {
+            window_size - prev + self.pos
+        } else {
+            self.pos - prev
+        };
+
+        let Some((number, prev_message)) = self.messages[ix].as_ref() else {
+            anyhow::bail!("Repeat index {prev} refers to an empty slot");
+        };
+        let prev_checksum =
+            crate::verify_checksum(&prev_message.try_to_string()?).map_or(0, |(_, c)| c);
+        let checksum = repeat.repeat().checksum();
+        if u32::from(prev_checksum) != checksum {
+            anyhow::bail!("Mismatched checksum: {prev_checksum} != {checksum}");
+        };
+        // The line ending belongs to this occurrence, not the original
+        let mut message = prev_message.clone();
+        message.line_ending = repeat.line_ending;
+        message.trailing_whitespace = repeat.trailing_whitespace.clone();
+        Ok((*number, message))
+    }
+
+    /// Records a decoded message, returning its number.
+    pub fn push(&mut self, message: spec::Message) -> u64 {
+        let number = self.count;
+        self.count += 1;
+        if !self.messages.is_empty() {
+            // Pre: 0 <= pos < window_size
+            self.messages[self.pos] = Some((number, message));
+            self.pos = (self.pos + 1) % self.messages.len();
+            // post: 0 <= pos < window_size
+        }
+        number
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repeat(index: i32, checksum: u8) -> spec::Message {
+        let mut r = spec::Repeat::new();
+        r.set_index(index);
+        r.set_checksum(checksum.into());
+        let mut m = spec::Message::new();
+        m.set_repeat(r);
+        m
+    }
+
+    #[test]
+    fn test_resolve() {
+        let line = "!AIVDM,1,1,,A,13HOI:0P0000VOHLCnHQKwvL05Ip,0*23";
+        let mut window = Window::new(4);
+        assert!(window.resolve(&repeat(1, 0x23)).is_err());
+
+        window.push(line.parse().unwrap());
+        window.push("other".parse().unwrap());
+        let (number, message) = window.resolve(&repeat(2, 0x23)).unwrap();
+        assert_eq!(number, 0);
+        assert_eq!(message.try_to_string().unwrap(), line);
+        assert!(window.resolve(&repeat(2, 0x24)).is_err());
+        assert!(window.resolve(&repeat(4, 0x23)).is_err());
+
+        // Wrap around so the original sits after the current position
+        window.push("a".parse().unwrap());
+        window.push("b".parse().unwrap());
+        assert_eq!(window.resolve(&repeat(3, 0)).unwrap().0, 1);
+
+        let mut crlf = repeat(1, 0);
+        crlf.set_line_ending(spec::LineEnding::CRLF);
+        let (number, message) = window.resolve(&crlf).unwrap();
+        assert_eq!(number, 3);
+        assert_eq!(message.line_ending(), spec::LineEnding::CRLF);
+
+        window.reset();
+        assert!(window.resolve(&repeat(1, 0)).is_err());
+        assert_eq!(window.push("c".parse().unwrap()), 4);
+    }
+}
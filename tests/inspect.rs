@@ -0,0 +1,24 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Runs `ais-inspect` over a stream, returning its stdout.
+fn inspect(stream: &[u8]) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ais-inspect"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(stream).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_empty_frame() {
+    // Empty header, an empty frame, then a raw "x"
+    let out = inspect(b"\x00\x00\x03\x0a\x01x");
+    assert!(out.contains("empty 1 bytes\n    undecodable"), "{out}");
+    assert!(out.contains("raw 4 bytes #0"), "{out}");
+    assert!(out.contains("\"x\\n\""), "{out}");
+}